@@ -0,0 +1,444 @@
+use crate::kurve::powerup::{PowerModifier, PowerRegistry};
+use crate::resource;
+use crate::resource::{Resource, ResourceCache};
+use ggez::audio::{SoundSource, Source};
+use ggez::{Context, GameError, GameResult};
+use macros::AssetBank;
+use rand::seq::SliceRandom;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// How much a volume changes with a single key press
+const VOLUME_STEP: f32 = 0.1;
+
+/// Looped in the main menu
+const MENU_TRACK: Resource = resource!("music/menu.wav");
+
+/// Tracks shuffled during the game
+const GAME_TRACKS: [Resource; 3] = [
+    resource!("music/game/drive.wav"),
+    resource!("music/game/spiral.wav"),
+    resource!("music/game/tangent.wav"),
+];
+
+/// How long an in-game track plays before fading to the next one
+const TRACK_DURATION: Duration = Duration::from_secs(90);
+
+/// How long it takes for one track to fade into another
+const CROSSFADE_DURATION: Duration = Duration::from_secs(3);
+
+/// Music volume multiplier when ducked
+const DUCK_VOLUME: f32 = 0.3;
+
+/// How long it takes to duck or restore the music
+const DUCK_DURATION: Duration = Duration::from_millis(400);
+
+/// Holds all the sound related state of the game
+#[derive(Debug)]
+pub struct Audio {
+    pub settings: AudioSettings,
+
+    sound_bank: SoundBank,
+
+    /// Pickup sounds, loaded for every modifier in the registry
+    powermod_sounds: ResourceCache<Source>,
+
+    music: Music,
+}
+
+impl Audio {
+    pub fn new(ctx: &mut Context, registry: &PowerRegistry) -> Result<Self, GameError> {
+        Ok(Self {
+            settings: AudioSettings::default(),
+            sound_bank: SoundBank::new(ctx)?,
+            powermod_sounds: ResourceCache::load(
+                ctx,
+                registry.modifiers().map(|modifier| modifier.sound()),
+                Resource::sound,
+            )?,
+            music: Music::new(ctx),
+        })
+    }
+
+    /// Progress music fades and switch tracks according to the mode
+    #[inline]
+    pub fn update(&mut self, ctx: &mut Context, mode: MusicMode) -> GameResult {
+        self.music.update(ctx, mode, &self.settings)
+    }
+
+    /// Play a sound effect with the current effects volume
+    pub fn play(&mut self, ctx: &mut Context, effect: SoundEffect) -> GameResult {
+        let volume = self.settings.effects_volume();
+
+        if volume <= 0. {
+            return Ok(());
+        }
+
+        let Some(source) = self.source(effect) else {
+            return Ok(());
+        };
+
+        // Detached sinks do not keep the source volume so it has to be set every time
+        source.set_volume(volume);
+        source.play_detached(ctx)
+    }
+
+    fn source(&mut self, effect: SoundEffect) -> Option<&mut Source> {
+        Some(match effect {
+            SoundEffect::CountdownTick => &mut self.sound_bank.countdown,
+            SoundEffect::RoundStart => &mut self.sound_bank.start,
+            SoundEffect::Death => &mut self.sound_bank.death,
+            SoundEffect::ShieldBreak => &mut self.sound_bank.shield_break,
+            SoundEffect::Winner => &mut self.sound_bank.winner,
+            SoundEffect::Powermod(pm) => return self.powermod_sounds.get_mut(pm.sound()),
+        })
+    }
+}
+
+/// Volume levels in the range `0.0..=1.0`
+#[derive(Debug, Clone, Copy)]
+pub struct AudioSettings {
+    /// Scales every other volume
+    pub master: f32,
+
+    /// Sound effects volume
+    pub effects: f32,
+
+    /// Music volume
+    pub music: f32,
+
+    /// Whether the music is silenced regardless of its volume
+    pub music_muted: bool,
+}
+
+impl AudioSettings {
+    /// The volume sound effects should be played at
+    #[inline]
+    pub fn effects_volume(&self) -> f32 {
+        self.master * self.effects
+    }
+
+    /// The volume music should be played at
+    #[inline]
+    pub fn music_volume(&self) -> f32 {
+        if self.music_muted {
+            0.
+        } else {
+            self.master * self.music
+        }
+    }
+
+    #[inline]
+    pub fn toggle_music_mute(&mut self) {
+        self.music_muted = !self.music_muted;
+    }
+
+    #[inline]
+    pub fn get(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Effects => self.effects,
+            VolumeChannel::Music => self.music,
+        }
+    }
+
+    /// Raise the channel's volume by a single step
+    pub fn increment(&mut self, channel: VolumeChannel) {
+        let volume = self.get_mut(channel);
+        *volume = (*volume + VOLUME_STEP).min(1.);
+    }
+
+    /// Lower the channel's volume by a single step
+    pub fn decrement(&mut self, channel: VolumeChannel) {
+        let volume = self.get_mut(channel);
+        *volume = (*volume - VOLUME_STEP).max(0.);
+    }
+
+    #[inline]
+    fn get_mut(&mut self, channel: VolumeChannel) -> &mut f32 {
+        match channel {
+            VolumeChannel::Master => &mut self.master,
+            VolumeChannel::Effects => &mut self.effects,
+            VolumeChannel::Music => &mut self.music,
+        }
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            effects: 0.8,
+            music: 0.5,
+            music_muted: false,
+        }
+    }
+}
+
+/// Adjustable volumes
+#[derive(Debug, Clone, Copy)]
+pub enum VolumeChannel {
+    Master,
+    Effects,
+    Music,
+}
+
+impl Display for VolumeChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VolumeChannel::Master => write!(f, "Master volume"),
+            VolumeChannel::Effects => write!(f, "Effects volume"),
+            VolumeChannel::Music => write!(f, "Music volume"),
+        }
+    }
+}
+
+/// Which music should currently be playing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MusicMode {
+    /// Loop the menu track
+    Menu,
+
+    /// Play the shuffled game playlist
+    Game {
+        /// Whether the music should be quieter than usual
+        ducked: bool,
+    },
+}
+
+impl MusicMode {
+    #[inline]
+    fn ducked(&self) -> bool {
+        matches!(self, Self::Game { ducked: true })
+    }
+}
+
+/// A playing music track
+#[derive(Debug)]
+struct Track {
+    source: Source,
+
+    /// Whether this is the menu track or one from the playlist
+    menu: bool,
+
+    started: Instant,
+
+    /// Crossfade volume multiplier in the range `0.0..=1.0`
+    fade: f32,
+}
+
+impl Track {
+    fn play(ctx: &mut Context, resource: Resource, menu: bool) -> Result<Self, GameError> {
+        let mut source = resource.sound(ctx)?;
+        source.set_repeat(true);
+        source.set_volume(0.);
+        source.play(ctx)?;
+
+        Ok(Self {
+            source,
+            menu,
+            started: Instant::now(),
+            fade: 0.,
+        })
+    }
+}
+
+/// Background music player. Fades between the menu track and a shuffled game playlist.
+/// Tracks missing from the resources directory are skipped, so the music is simply silent without
+/// them. With the `embed` feature they are compiled in like every other resource.
+#[derive(Debug)]
+struct Music {
+    /// Looped in the main menu, if present
+    menu: Option<Resource>,
+
+    /// Game tracks in the order they will be played
+    playlist: Vec<Resource>,
+
+    /// Index of the next playlist track
+    next: usize,
+
+    /// The track fading in or playing at full volume
+    current: Option<Track>,
+
+    /// Previous tracks that are still fading out
+    fading: Vec<Track>,
+
+    /// Ducking volume multiplier in the range `DUCK_VOLUME..=1.0`
+    duck: f32,
+}
+
+impl Music {
+    fn new(ctx: &mut Context) -> Self {
+        let playlist = GAME_TRACKS
+            .into_iter()
+            .filter(|track| track.exists(ctx))
+            .collect();
+
+        let menu = MENU_TRACK.exists(ctx).then_some(MENU_TRACK);
+
+        let mut music = Self {
+            menu,
+            playlist,
+            next: 0,
+            current: None,
+            fading: vec![],
+            duck: 1.,
+        };
+
+        music.shuffle();
+
+        music
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        mode: MusicMode,
+        settings: &AudioSettings,
+    ) -> GameResult {
+        let delta = ctx.time.delta().as_secs_f32();
+
+        let switch = match (&self.current, mode) {
+            (None, MusicMode::Menu) => self.menu.is_some(),
+            (None, MusicMode::Game { .. }) => !self.playlist.is_empty(),
+            (Some(track), MusicMode::Menu) => !track.menu,
+            (Some(track), MusicMode::Game { .. }) => {
+                track.menu || Instant::now().duration_since(track.started) >= TRACK_DURATION
+            }
+        };
+
+        if switch {
+            if let Some(track) = self.current.take() {
+                self.fading.push(track);
+            }
+            self.current = self.next_track(ctx, mode)?;
+        }
+
+        // Crossfade
+
+        let fade_step = delta / CROSSFADE_DURATION.as_secs_f32();
+
+        if let Some(ref mut track) = self.current {
+            track.fade = (track.fade + fade_step).min(1.);
+        }
+
+        for track in self.fading.iter_mut() {
+            track.fade -= fade_step;
+        }
+
+        // Dropping a source stops it
+        self.fading.retain(|track| track.fade > 0.);
+
+        // Ducking
+
+        let duck_step = delta * (1. - DUCK_VOLUME) / DUCK_DURATION.as_secs_f32();
+
+        self.duck = if mode.ducked() {
+            (self.duck - duck_step).max(DUCK_VOLUME)
+        } else {
+            (self.duck + duck_step).min(1.)
+        };
+
+        let volume = settings.music_volume() * self.duck;
+
+        for track in self.current.iter_mut().chain(self.fading.iter_mut()) {
+            track.source.set_volume(volume * track.fade);
+        }
+
+        Ok(())
+    }
+
+    /// Start the track that should play for the given mode
+    fn next_track(
+        &mut self,
+        ctx: &mut Context,
+        mode: MusicMode,
+    ) -> Result<Option<Track>, GameError> {
+        match mode {
+            MusicMode::Menu => match self.menu {
+                Some(menu) => Ok(Some(Track::play(ctx, menu, true)?)),
+                None => Ok(None),
+            },
+            MusicMode::Game { .. } => {
+                if self.playlist.is_empty() {
+                    return Ok(None);
+                }
+
+                if self.next >= self.playlist.len() {
+                    self.shuffle();
+                }
+
+                let track = Track::play(ctx, self.playlist[self.next], false)?;
+                self.next += 1;
+
+                Ok(Some(track))
+            }
+        }
+    }
+
+    /// Shuffle the playlist and start from the beginning
+    fn shuffle(&mut self) {
+        self.playlist.shuffle(&mut rand::thread_rng());
+        self.next = 0;
+    }
+}
+
+/// All the sounds the game can make
+#[derive(Debug, Clone, Copy)]
+pub enum SoundEffect {
+    /// A second passed in the round countdown
+    CountdownTick,
+
+    /// The countdown is done and the round is running
+    RoundStart,
+
+    /// A curve picked up a powermod
+    Powermod(&'static dyn PowerModifier),
+
+    /// A curve collided with something
+    Death,
+
+    /// A shield saved a curve from a collision
+    ShieldBreak,
+
+    /// A player won the round
+    Winner,
+}
+
+/// Sounds that do not belong to a powermod
+#[derive(Debug, AssetBank)]
+struct SoundBank {
+    #[sound(path = "sfx/countdown.wav")]
+    countdown: Source,
+    #[sound(path = "sfx/start.wav")]
+    start: Source,
+    #[sound(path = "sfx/death.wav")]
+    death: Source,
+    #[sound(path = "sfx/winner.wav")]
+    winner: Source,
+    #[sound(path = "sfx/shield_break.wav")]
+    shield_break: Source,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn every_game_track_is_in_the_playlist() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/music/game");
+
+        for entry in std::fs::read_dir(dir).expect("the game tracks directory should exist") {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+
+            assert!(
+                GAME_TRACKS
+                    .iter()
+                    .any(|track| track.path == format!("/music/game/{name}")),
+                "{name} is missing from GAME_TRACKS"
+            );
+        }
+    }
+}
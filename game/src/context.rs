@@ -1,16 +1,20 @@
-use crate::kurve::Kurve;
+use crate::audio::{Audio, MusicMode};
+use crate::kurve::{editor::MapEditor, Kurve, KurvePhase};
 use crate::menu::{MainMenu, MainMenuItem};
-use ggez::audio::{SoundSource, Source};
 use ggez::event::{self};
-use ggez::graphics::{self, Color, Image};
+use ggez::graphics::{self, Color};
 use ggez::input::keyboard::{KeyCode, KeyInput};
 use ggez::{Context, GameError, GameResult};
 use std::fmt::Debug;
 
+/// Toggles the music on and off
+const MUTE_KEY: KeyCode = KeyCode::F8;
+
 #[derive(Debug)]
 enum GameState {
     MainMenu,
     Kurve,
+    Editor,
 }
 
 pub struct Game {
@@ -18,38 +22,73 @@ pub struct Game {
 
     kurve: Kurve,
 
+    editor: MapEditor,
+
+    audio: Audio,
+
     state: GameState,
 }
 
 impl Game {
     pub fn new(ctx: &mut Context) -> Result<Self, GameError> {
         let kurve = Kurve::new(ctx)?;
-        /*         Source::new(ctx, "/httm.mp3")
-        .unwrap()
-        .play_detached(ctx)
-        .unwrap(); */
         Ok(Self {
             main_menu: MainMenu::new(),
+            editor: MapEditor::new(),
+            audio: Audio::new(ctx, &kurve.powers.registry)?,
             kurve,
             state: GameState::MainMenu,
         })
     }
 }
 
+impl Game {
+    /// The music that fits the current state
+    fn music_mode(&self) -> MusicMode {
+        match self.state {
+            GameState::MainMenu | GameState::Editor => MusicMode::Menu,
+            GameState::Kurve => MusicMode::Game {
+                ducked: matches!(self.kurve.state, KurvePhase::Winner { .. }),
+            },
+        }
+    }
+}
+
 impl event::EventHandler for Game {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
+        if ctx.keyboard.is_key_just_pressed(MUTE_KEY) {
+            self.audio.settings.toggle_music_mute();
+        }
+
+        self.audio.update(ctx, self.music_mode())?;
+
         match self.state {
             GameState::MainMenu => {
+                self.main_menu.navigate(ctx, &mut self.audio.settings);
+
                 if !ctx.keyboard.is_key_just_pressed(KeyCode::Return) {
                     return Ok(());
                 }
 
                 match self.main_menu.items[self.main_menu.selected] {
-                    MainMenuItem::PlayButton { .. } => self.state = GameState::Kurve,
+                    MainMenuItem::PlayButton { .. } => {
+                        self.kurve.reload_maps(ctx);
+                        self.state = GameState::Kurve;
+                    }
+                    MainMenuItem::EditorButton { .. } => {
+                        self.editor = MapEditor::new();
+                        self.state = GameState::Editor;
+                    }
+                    MainMenuItem::Volume { .. } => {}
                 }
             }
             GameState::Kurve => {
-                self.kurve.update(ctx)?;
+                self.kurve.update(ctx, &mut self.audio)?;
+            }
+            GameState::Editor => {
+                if self.editor.update(ctx)? {
+                    self.state = GameState::MainMenu;
+                }
             }
         }
 
@@ -60,8 +99,11 @@ impl event::EventHandler for Game {
         let mut canvas = graphics::Canvas::from_frame(ctx, Some(Color::BLACK));
 
         match self.state {
-            GameState::MainMenu => self.main_menu.draw(ctx, &mut canvas)?,
+            GameState::MainMenu => self
+                .main_menu
+                .draw(ctx, &mut canvas, &self.audio.settings)?,
             GameState::Kurve => self.kurve.draw(ctx, &mut canvas)?,
+            GameState::Editor => self.editor.draw(ctx, &mut canvas)?,
         }
 
         canvas.finish(ctx)?;
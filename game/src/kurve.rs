@@ -1,40 +1,70 @@
 use self::menu::{KurveMenu, KurveMenuItem, PlayerConfig, PlayerConfigFocus};
 use self::point::{BoundingCircle, Girth};
-use self::powerup::{PowerMod, PowerModifier, PowerTimeout};
+use self::powerup::{PowerMod, PowerModifier, PowerRegistry, PowerTarget, PowerTimeout, Reverse};
+use crate::audio::{Audio, SoundEffect};
+use crate::resource::{Resource, ResourceCache};
 use curve::MoveKeys;
+use ggez::glam;
 use ggez::graphics::{Drawable, Image, PxScale};
 use ggez::input::keyboard::KeyCode;
-use ggez::{glam, GameError};
+use ggez::GameError;
 use ggez::{
     graphics::{self, Canvas, Color, DrawParam},
     mint::Point2,
     Context, GameResult,
 };
-use macros::ImageBank;
+use macros::AssetBank;
+use map::{Map, MapError};
+use obstacle::{Obstacle, OBSTACLE_GIRTH};
 use player::Player;
 use point::Line;
+use portal::Portal;
 use rand::distributions::uniform::SampleUniform;
+use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
+use shape::ArenaShape;
 use std::collections::HashMap;
-use std::f32::consts::{FRAC_PI_8, PI};
-use std::fmt::{Debug, Write};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_8, PI};
+use std::fmt::Debug;
 use std::time::{Duration, Instant};
-use {curve::Curve, point::BoundingBox};
+use {
+    curve::{Curve, Effect},
+    point::BoundingBox,
+};
 
 mod curve;
+pub mod editor;
+mod hazard;
+mod map;
 mod menu;
+mod obstacle;
 mod player;
 mod point;
-mod powerup;
+mod portal;
+pub mod powerup;
+mod shape;
+mod trail;
 
 // const CURVE_SIZE: f32 = 2.;
 
 /// Default rotation speed
 const DEFAULT_ROTATION: f32 = FRAC_PI_8 * 0.1;
 
+/// Slowest rotation speed powermods can bring a curve to
+const MIN_ROTATION: f32 = 0.01;
+
+/// Fastest rotation speed powermods can bring a curve to
+const MAX_ROTATION: f32 = 0.15;
+
 /// Default velocity
 const DEFAULT_VELOCITY: f32 = 60.;
 
+/// Slowest velocity powermods can bring a curve to
+const MIN_VELOCITY: f32 = 20.;
+
+/// Fastest velocity powermods can bring a curve to
+const MAX_VELOCITY: f32 = 200.;
+
 /// Default girth
 const DEFAULT_GIRTH: Girth = Girth::Normal;
 
@@ -44,17 +74,35 @@ const TRAIL_SKIP_MIN: u64 = 2000;
 /// The maximum amount a curve will draw a trail in miliseconds
 const TRAIL_SKIP_MAX: u64 = 4000;
 
-/// 2-3 players
+/// 2-3 players, also the size of the arena preview during setup
 const SIZE_SMALL: (f32, f32) = (0.35, 0.55);
 
-/// 4-6 players
+// 4-6 players
 // const SIZE_MED: (f32, f32) = (0.5, 0.5);
 
+/// How long the winner is shown before the next round's countdown starts
 const WINNER_GLOAT_DURATION: Duration = Duration::from_secs(3);
 
+/// Round times after which the arena starts shrinking to choose from in the setup menu
+const SHRINK_OPTIONS: [Option<Duration>; 4] = [
+    None,
+    Some(Duration::from_secs(30)),
+    Some(Duration::from_secs(60)),
+    Some(Duration::from_secs(120)),
+];
+
+/// How fast the arena borders move inward during sudden death, in pixels per second
+const SHRINK_SPEED: f32 = 8.;
+
+/// The smallest the arena gets during sudden death, as a fraction of its width and height
+const SHRINK_MIN_SIZE: (f32, f32) = (0.2, 0.2);
+
 /// Curve invulnerability duration when it is not leaving the trail
 const INV_DURATION: Duration = Duration::from_millis(300);
 
+/// How long a curve saved by its shield passes through trails to get away from the one it hit
+const SHIELD_GRACE: Duration = Duration::from_millis(300);
+
 const COLORS: [Color; 5] = [
     Color::GREEN,
     Color::YELLOW,
@@ -97,20 +145,56 @@ const MIN_POWERMOD_FUSE: u64 = 3_000;
 /// Max amount of time of no powermod spawnage
 const MAX_POWERMOD_FUSE: u64 = 6_000;
 
-const POWERMODS: [PowerModifier; 1] = [
-    // PowerModifier::SpeedUp,
-    // PowerModifier::SpeedDown,
-    // PowerModifier::RotUp,
-    // PowerModifier::RotDown,
-    PowerModifier::Chungus,
-    // PowerModifier::Anorexia,
-    // PowerModifier::Invulnerability,
-];
-
+/// Default duration of a powermod's effect
 const POWERMOD_DURATION: Duration = Duration::from_millis(30000);
 
 const POWERMOD_SIZE: f32 = 16.;
 
+/// Maximum amount of powermods in the arena at once
+const MAX_POWERMODS: usize = 10;
+
+/// How many random points are tried when looking for a spot to spawn a powermod
+const SPAWN_ATTEMPTS: usize = 20;
+
+/// Minimum distance between the edge of a spawned powermod and the arena walls
+const SPAWN_WALL_DISTANCE: f32 = 10.;
+
+/// Minimum distance between a spawned curve and the arena walls or any obstacle
+const SPAWN_OBSTACLE_DISTANCE: f32 = 30.;
+
+/// How many random points are tried when looking for a spot to place a curve
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+/// Minimum distance between the edge of a spawned powermod and any curve's head
+const SPAWN_CURVE_DISTANCE: f32 = 60.;
+
+/// Minimum distance between the edge of a spawned powermod and any trail
+const SPAWN_TRAIL_DISTANCE: f32 = 8.;
+
+/// Distance from a curve with an active magnet at which powermods start getting pulled
+const MAGNET_RADIUS: f32 = 180.;
+
+/// How fast powermods accelerate towards a magnet per active magnet, in pixels per second squared
+const MAGNET_ACCELERATION: f32 = 160.;
+
+/// The fastest a pulled powermod can drift in pixels per second
+const MAGNET_MAX_SPEED: f32 = 120.;
+
+/// Fraction of its velocity a powermod loses per second once nothing is pulling it
+const MAGNET_FRICTION: f32 = 2.;
+
+/// How long a powermod is announced before it can be collected
+const SPAWN_TELEGRAPH_DURATION: Duration = Duration::from_millis(700);
+
+/// Height of a single player's row in the score panel
+const HUD_ROW_HEIGHT: f32 = 30.;
+
+/// Radius of the effect icons in the score panel
+const HUD_ICON_RADIUS: f32 = 11.;
+
+/// How long the name of a picked up effect is shown in the score panel
+const HUD_ANNOUNCE_DURATION: Duration = Duration::from_millis(1500);
+
 /// Multipliers for the x and y axis used to position the kurve area during setup
 const SETUP_KURVE_CENTER: (f32, f32) = (0.7, 0.5);
 
@@ -119,6 +203,20 @@ const SETUP_MENU_CENTER: (f32, f32) = (0.3, 0.5);
 
 const PAUSE_MENU_CENTER: (f32, f32) = (0.5, 0.5);
 
+const OBSTACLE_COLOR: Color = Color {
+    r: 0.43,
+    g: 0.43,
+    b: 0.47,
+    a: 1.,
+};
+
+const HAZARD_COLOR: Color = Color {
+    r: 0.85,
+    g: 0.35,
+    b: 0.2,
+    a: 1.,
+};
+
 /// Represents the current phase of the game
 #[derive(Debug)]
 pub enum KurvePhase {
@@ -153,6 +251,11 @@ pub struct Kurve {
     /// Where the arena starts and ends on each axis
     pub bounds: ArenaBounds,
 
+    /// The space the map is placed in, see [ArenaBounds::place]. The same as
+    /// [bounds][Self::bounds] until the arena starts shrinking in sudden death, after which it
+    /// keeps the size the round started with.
+    pub arena: ArenaBounds,
+
     /// Players involved in the game
     pub players: Vec<Player>,
 
@@ -167,43 +270,36 @@ pub struct Kurve {
 
     pub menu: KurveMenu,
 
-    image_bank: ImageBank,
-}
+    /// Match options chosen during setup
+    pub rules: Rules,
+
+    /// How long the current round has been running, excluding pauses
+    pub round_time: Duration,
+
+    /// Maps to choose from in the setup menu
+    pub maps: Vec<Map>,
+
+    /// Map files that failed to load, listed under the map picker during setup
+    pub map_errors: Vec<MapError>,
+
+    assets: Assets,
 
-#[derive(Debug, ImageBank)]
-pub struct ImageBank {
-    #[image(path = "sprint.png")]
-    speed_up: Image,
-    #[image(path = "turtle.png")]
-    speed_down: Image,
-    #[image(path = "battery-plus.png")]
-    rot_up: Image,
-    #[image(path = "battery-minus.png")]
-    rot_down: Image,
-    #[image(path = "evil-wings.png")]
-    invuln: Image,
-    #[image(path = "boar.png")]
-    chungus: Image,
-    #[image(path = "earth-worm.png")]
-    anorx: Image,
+    /// Powermod icons, loaded for every registered modifier
+    icons: ResourceCache<Image>,
 }
 
-impl ImageBank {
-    fn get_powermod(&self, pm: PowerModifier) -> &Image {
-        match pm {
-            PowerModifier::SpeedUp => &self.speed_up,
-            PowerModifier::SpeedDown => &self.speed_down,
-            PowerModifier::RotUp => &self.rot_up,
-            PowerModifier::RotDown => &self.rot_down,
-            PowerModifier::Anorexia => &self.anorx,
-            PowerModifier::Chungus => &self.chungus,
-            PowerModifier::Invulnerability => &self.invuln,
-        }
-    }
+/// Assets used while drawing the game that do not belong to a powermod
+#[derive(Debug, AssetBank)]
+pub struct Assets {
+    #[font(path = "fonts/DejaVuSansMono-Bold.ttf")]
+    bold: &'static str,
 }
 
 #[derive(Debug)]
 pub struct PowerSupply {
+    /// The modifiers powermods spawn with
+    pub registry: PowerRegistry,
+
     pub powermods: HashMap<usize, PowerMod>,
 
     pub power_timeouts: Vec<PowerTimeout>,
@@ -216,15 +312,27 @@ pub struct PowerSupply {
 }
 
 impl PowerSupply {
-    pub fn tick_powermods(&mut self, bounds: ArenaBounds) {
+    /// Spawn a powermod once the fuse runs out. Powermods are placed in `arena` and have to be
+    /// inside `border`, see [Map::fits].
+    pub fn tick_powermods(
+        &mut self,
+        arena: ArenaBounds,
+        border: ArenaBounds,
+        curves: &[Curve],
+        rules: &Rules,
+    ) {
         if Instant::now().duration_since(self.last_powermod) >= self.power_fuse {
-            if self.powermods.len() < 10 {
-                let r = thread_rng().gen_range(0..POWERMODS.len());
-                let powermod = PowerMod::new(bounds.random_pos(), POWERMODS[r]);
+            if self.powermods.len() < MAX_POWERMODS {
+                let entry = self.registry.random(rules.arena_mode());
+                let point = self.spawn_point(arena, border, rules, curves);
+
+                if let (Some(entry), Some(point)) = (entry, point) {
+                    let powermod = PowerMod::new(point, entry);
 
-                // Insert and increment
-                self.powermods.insert(self.last_id, powermod);
-                self.last_id += 1;
+                    // Insert and increment
+                    self.powermods.insert(self.last_id, powermod);
+                    self.last_id += 1;
+                }
             }
             self.last_powermod = Instant::now();
             self.power_fuse =
@@ -232,6 +340,106 @@ impl PowerSupply {
         }
     }
 
+    /// Pull collectible powermods towards the closest curve with an active magnet in range.
+    /// Powermods out of reach slow down and come to a stop.
+    pub fn tick_magnets(
+        &mut self,
+        arena: ArenaBounds,
+        border: ArenaBounds,
+        rules: &Rules,
+        curves: &[Curve],
+        delta: f32,
+    ) {
+        let now = Instant::now();
+
+        for powermod in self.powermods.values_mut() {
+            if !powermod.collectible(now) {
+                continue;
+            }
+
+            let magnet = curves
+                .iter()
+                .filter(|curve| curve.alive && curve.has_effect(Effect::Magnet))
+                .map(|curve| (curve, distance(curve.position, powermod.point)))
+                .filter(|(_, dist)| *dist <= MAGNET_RADIUS)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            match magnet {
+                Some((curve, dist)) if dist > 0. => {
+                    let direction = glam::Vec2::new(
+                        curve.position.x - powermod.point.x,
+                        curve.position.y - powermod.point.y,
+                    ) / dist;
+
+                    let pull = MAGNET_ACCELERATION * curve.effect_count(Effect::Magnet) as f32;
+                    powermod.velocity = (powermod.velocity + direction * pull * delta)
+                        .clamp_length_max(MAGNET_MAX_SPEED);
+                }
+                _ => {
+                    powermod.velocity *= (1. - MAGNET_FRICTION * delta).max(0.);
+                }
+            }
+
+            let previous = powermod.point;
+            powermod.mv(delta);
+
+            // Stop at the border and at obstacles
+            if !rules.map.fits(arena, border, powermod.point, POWERMOD_SIZE) {
+                powermod.point = previous;
+                powermod.velocity = glam::Vec2::ZERO;
+            }
+        }
+    }
+
+    /// Find a random point far enough from the walls, curves, trails and other powermods.
+    /// Gives up after a few attempts, in which case nothing should spawn.
+    fn spawn_point(
+        &self,
+        arena: ArenaBounds,
+        border: ArenaBounds,
+        rules: &Rules,
+        curves: &[Curve],
+    ) -> Option<Point2<f32>> {
+        let wall_distance = POWERMOD_SIZE + SPAWN_WALL_DISTANCE;
+
+        (0..SPAWN_ATTEMPTS)
+            .map(|_| rules.map.powermod_pos(arena))
+            .find(|point| {
+                let inside = rules.map.fits(arena, border, *point, wall_distance);
+
+                let far_from_curves = curves.iter().all(|curve| {
+                    distance(*point, curve.position) >= POWERMOD_SIZE + SPAWN_CURVE_DISTANCE
+                });
+
+                let far_from_trails = curves.iter().all(|curve| {
+                    curve
+                        .trail
+                        .iter()
+                        .flat_map(|line| line.iter())
+                        .all(|trail_point| {
+                            distance(*point, *trail_point) >= POWERMOD_SIZE + SPAWN_TRAIL_DISTANCE
+                        })
+                });
+
+                let far_from_powermods = self
+                    .powermods
+                    .values()
+                    .all(|powermod| distance(*point, powermod.point) >= POWERMOD_SIZE * 3.);
+
+                inside && far_from_curves && far_from_trails && far_from_powermods
+            })
+    }
+
+    /// Take out the oldest timeout of a modifier absorbing collisions on the curve, if any.
+    /// The caller is responsible for removing its modification.
+    fn take_shield(&mut self, curve: usize) -> Option<PowerTimeout> {
+        let i = self
+            .power_timeouts
+            .iter()
+            .position(|timeout| timeout.curve == curve && timeout.ty.absorbs_collision())?;
+        Some(self.power_timeouts.remove(i))
+    }
+
     /// Completely reset the powermods state
     fn reset_powermods(&mut self) {
         self.power_fuse =
@@ -242,9 +450,10 @@ impl PowerSupply {
         self.last_id = 0;
     }
 
-    fn add_timeout(&mut self, curve: usize, ty: PowerModifier) {
+    fn add_timeout(&mut self, curve: usize, target: PowerTarget, ty: &'static dyn PowerModifier) {
         self.power_timeouts.push(PowerTimeout {
             curve,
+            target,
             started: Instant::now(),
             ty,
         });
@@ -275,6 +484,15 @@ impl Kurve {
             selected: PlayerConfigFocus::Name,
         };
 
+        let (maps, map_errors) = Map::load_all(ctx);
+
+        let registry = PowerRegistry::default();
+        let icons = ResourceCache::load(
+            ctx,
+            registry.modifiers().map(|modifier| modifier.icon()),
+            Resource::image,
+        )?;
+
         let bounds = ArenaBounds::new(
             Point2 {
                 x: size.0 * SETUP_KURVE_CENTER.0,
@@ -291,6 +509,7 @@ impl Kurve {
 
         Ok(Self {
             bounds,
+            arena: bounds,
             curves: vec![curve1, curve2],
             players: vec![player1, player2],
             state: KurvePhase::Setup,
@@ -299,27 +518,38 @@ impl Kurve {
                     KurveMenuItem::PlayerCurveConfig(config1),
                     KurveMenuItem::PlayerCurveConfig(config2),
                     KurveMenuItem::AddPlayer,
+                    KurveMenuItem::Wrap,
+                    KurveMenuItem::SuddenDeath,
+                    KurveMenuItem::Map,
                     KurveMenuItem::Start,
                 ],
-                selected: 3,
+                selected: 6,
                 colors,
                 keys,
                 active_mod: None,
             },
             powers: PowerSupply {
-                powermods: HashMap::with_capacity(10),
+                registry,
+                powermods: HashMap::with_capacity(MAX_POWERMODS),
                 power_timeouts: Vec::with_capacity(20),
                 power_fuse: Duration::MAX,
                 last_powermod: Instant::now(),
                 last_id: 0,
             },
 
-            image_bank: ImageBank::new(ctx)?,
+            rules: Rules::default(),
+            round_time: Duration::ZERO,
+
+            maps,
+            map_errors,
+
+            assets: Assets::new(ctx)?,
+            icons,
         })
     }
 
     /// Update the game state
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult {
+    pub fn update(&mut self, ctx: &mut Context, audio: &mut Audio) -> GameResult {
         if ctx.keyboard.is_key_just_pressed(KeyCode::Space) {
             self.toggle_pause();
         }
@@ -332,15 +562,16 @@ impl Kurve {
                 self.tick_setup_curves(ctx, delta);
             }
             KurvePhase::Running => {
-                if let Some(winner) = self.tick_running(ctx, delta)? {
+                if let Some(winner) = self.tick_running(ctx, audio, delta)? {
                     self.state = KurvePhase::Winner {
                         started: Instant::now(),
                         id: winner,
                     };
                     self.players[winner].score += 1;
+                    audio.play(ctx, SoundEffect::Winner)?;
                 }
             }
-            KurvePhase::StartCountdown { started } => self.tick_countdown(ctx, started),
+            KurvePhase::StartCountdown { started } => self.tick_countdown(ctx, audio, started)?,
             KurvePhase::Winner { started, .. } => self.tick_winner(delta, ctx, started),
             KurvePhase::Paused => {
                 self.tick_setup_menu(ctx)?;
@@ -360,102 +591,122 @@ impl Kurve {
     }
 
     /// Process a running game's tick
-    fn tick_running(&mut self, ctx: &mut Context, delta: f32) -> Result<Option<usize>, GameError> {
+    fn tick_running(
+        &mut self,
+        ctx: &mut Context,
+        audio: &mut Audio,
+        delta: f32,
+    ) -> Result<Option<usize>, GameError> {
         // Bitflags for collision
         let mut collisions = 0u8;
 
-        // Holds the curve index, powermod index and powermod type in that order
+        // Bitflags for collisions with the arena borders
+        let mut border_collisions = 0u8;
+
+        // Holds the collecting curve index, powermod index, powermod type and target in that order
         let mut apply_power_mods = vec![];
 
-        self.powers.tick_powermods(self.bounds);
+        self.tick_sudden_death(ctx);
 
-        for (i, curve) in self.curves.iter().enumerate() {
-            let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+        self.powers
+            .tick_powermods(self.arena, self.bounds, &self.curves, &self.rules);
+        self.powers
+            .tick_magnets(self.arena, self.bounds, &self.rules, &self.curves, delta);
 
-            // Powermods
-            for (id, powermod) in self.powers.powermods.iter() {
-                let p_bounds = powermod.bounds();
-                'curve_bbox: for curve_p in bbox {
-                    // First check the insides and only then the bbox
-                    if curve_p.x >= p_bounds.0
-                        && curve_p.x <= p_bounds.1
-                        && curve_p.y >= p_bounds.2
-                        && curve_p.y <= p_bounds.3
-                    {
-                        apply_power_mods.push((i, *id, powermod.ty));
-                        break;
-                    }
-                    // Check the bounding box
-                    for point in powermod.bbox.0.iter() {
-                        if point.x == curve_p.x && point.y == curve_p.y {
-                            apply_power_mods.push((i, *id, powermod.ty));
-                            break 'curve_bbox;
-                        }
-                    }
-                }
-            }
+        let now = Instant::now();
 
-            // If this is true, curve is invulnerable
-            if !curve.trail_active {
-                continue;
+        // Move curves passing through the borders to the opposite side before checking
+        // any collisions
+        // Borders are solid during sudden death so the shrinking arena can kill
+        if !self.sudden_death() {
+            for curve in self.curves.iter_mut() {
+                if curve.alive && self.rules.wraps(curve) {
+                    self.rules.map.shape.wrap(self.bounds, curve, delta);
+                }
             }
+        }
 
-            // Check collisions
-
-            if check_border_collision(
-                self.bounds.x_min,
-                self.bounds.x_max,
-                self.bounds.y_min,
-                self.bounds.y_max,
-                bbox,
-            ) {
-                collisions |= 1 << i;
-                continue;
+        // Jump through portals before checking collisions as well, the trail line pushed this
+        // tick then starts at the exit instead of spanning the whole jump
+        for curve in self.curves.iter_mut() {
+            if curve.alive {
+                self.rules.map.enter_portal(self.arena, curve, delta, now);
             }
+        }
 
-            for (j, curve) in self.curves.iter().enumerate() {
-                let lines = &curve.lines;
-
-                // Skip the last few lines of the current curve due to self collision
-                let line_count = if i == j {
-                    lines
-                        .len()
-                        .saturating_sub(15 * (curve.girth.as_f32() as usize).saturating_sub(1))
-                } else {
-                    lines.len()
-                };
+        for (i, curve) in self.curves.iter().enumerate() {
+            let next_pos = curve.next_pos(delta);
 
-                // Check for line collisions
-                for (_, line) in lines
-                    .iter()
-                    .enumerate()
-                    .take_while(|(i, _)| *i < line_count)
+            // Powermods, only living curves can pick them up
+            for (id, powermod) in self.powers.powermods.iter() {
+                if curve.alive
+                    && powermod.collectible(now)
+                    && powermod.hit(next_pos, curve.girth.as_f32())
                 {
-                    if check_bbox_colision(bbox, line) {
-                        collisions |= 1 << i;
-                    }
+                    apply_power_mods.push((i, *id, powermod.ty, powermod.target));
                 }
             }
         }
 
         // Remove pending powermods
         self.powers.power_timeouts.retain(|timeout| {
-            let expired = Instant::now().duration_since(timeout.started) >= POWERMOD_DURATION;
-            if expired {
-                timeout
-                    .ty
-                    .remove(ctx, &mut self.curves[timeout.curve])
-                    .unwrap(); // TODO: see if we can avoid this
+            let expired = timeout.expired(now);
+            let curve = &mut self.curves[timeout.curve];
+            // Dead curves stay as they are until the round resets them
+            if expired && curve.alive {
+                timeout.ty.remove(ctx, curve).unwrap(); // TODO: see if we can avoid this
             }
             !expired
         });
 
-        // Apply powermods
+        // Apply powermods before checking collisions, so curves that swapped places are
+        // checked where they ended up
+
+        for (collector, power, powermod, target) in apply_power_mods {
+            // The same powermod can be hit by multiple curves in the same tick
+            let Some(PowerMod { point, .. }) = self.powers.powermods.remove(&power) else {
+                continue;
+            };
+
+            powermod.apply_to_curves(ctx, collector, &mut self.curves)?;
+
+            for (i, curve) in self.curves.iter_mut().enumerate() {
+                if (curve.alive || powermod.affects_dead()) && target.affects(collector, i) {
+                    powermod.apply(ctx, curve, point)?;
+                    self.powers.add_timeout(i, target, powermod);
+                }
+            }
+
+            audio.play(ctx, SoundEffect::Powermod(powermod))?;
+        }
+
+        // Hazards kill the same way static obstacles do, wherever they got to this tick
+        let mut obstacles = self.rules.map.obstacle_lines(self.arena);
+        obstacles.extend(self.rules.map.hazard_lines(
+            self.arena,
+            self.round_time.as_secs_f32(),
+            self.rules.seed,
+        ));
 
-        for (curve, power, powermod) in apply_power_mods {
-            powermod.apply(ctx, &mut self.curves[curve])?;
-            self.powers.powermods.remove(&power);
-            self.powers.add_timeout(curve, powermod);
+        for (i, curve) in self.curves.iter().enumerate() {
+            let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+
+            match detect_collision(
+                &self.rules.map.shape,
+                self.bounds,
+                &obstacles,
+                &self.curves,
+                i,
+                bbox,
+                now,
+            ) {
+                Some(Hit::Border) => {
+                    collisions |= 1 << i;
+                    border_collisions |= 1 << i;
+                }
+                Some(Hit::Solid) => collisions |= 1 << i,
+                None => {}
+            }
         }
 
         // Apply collisions
@@ -463,10 +714,24 @@ impl Kurve {
             if !curve.alive {
                 continue;
             }
-            if collisions >> i == 1 {
-                curve.velocity = 0.;
-                curve.alive = false;
+            if collisions & (1 << i) == 0 {
+                continue;
             }
+
+            // Shields absorb the hit and give the curve a moment to get away
+            if let Some(shield) = self.powers.take_shield(i) {
+                shield.ty.remove(ctx, curve)?;
+                if border_collisions & (1 << i) != 0 {
+                    self.rules.map.shape.bounce(self.bounds, curve, delta);
+                }
+                curve.shield_break = Some(Instant::now());
+                audio.play(ctx, SoundEffect::ShieldBreak)?;
+                continue;
+            }
+
+            curve.velocity = 0.;
+            curve.alive = false;
+            audio.play(ctx, SoundEffect::Death)?;
         }
 
         // Check for winners
@@ -486,19 +751,59 @@ impl Kurve {
         Ok(None)
     }
 
+    /// Whether the round went on long enough for the arena to start shrinking
+    #[inline]
+    fn sudden_death(&self) -> bool {
+        self.rules
+            .shrink_after
+            .is_some_and(|after| self.round_time >= after)
+    }
+
+    /// Track the round time and shrink the arena towards its center once sudden death begins.
+    /// Curves left outside the new border die in the regular border collision check and
+    /// powermods left outside are removed.
+    fn tick_sudden_death(&mut self, ctx: &mut Context) {
+        self.round_time += ctx.time.delta();
+
+        let Some(after) = self.rules.shrink_after else {
+            return;
+        };
+
+        if self.round_time < after {
+            return;
+        }
+
+        let shrunk = (self.round_time - after).as_secs_f32() * SHRINK_SPEED;
+        self.bounds = self.arena.shrink(shrunk, SHRINK_MIN_SIZE);
+
+        let (arena, border, rules) = (self.arena, self.bounds, &self.rules);
+        self.powers
+            .powermods
+            .retain(|_, powermod| rules.map.fits(arena, border, powermod.point, POWERMOD_SIZE));
+    }
+
     fn tick_winner(&mut self, delta: f32, ctx: &mut Context, started: Instant) {
         let now = Instant::now();
 
+        let sudden_death = self.sudden_death();
+
         // Process movement
         for curve in self.curves.iter_mut() {
+            if self.rules.wraps(curve) && !sudden_death {
+                self.rules.map.shape.wrap(self.bounds, curve, delta);
+            }
+            self.rules.map.enter_portal(self.arena, curve, delta, now);
             curve.rotate(ctx);
             curve.tick_trail(delta);
             curve.mv(delta);
         }
 
         if now.duration_since(started) >= WINNER_GLOAT_DURATION {
+            // Restore the arena in case it shrunk
+            self.set_running_bounds(ctx.gfx.drawable_size());
             self.reset_curves();
             self.powers.reset_powermods();
+            self.round_time = Duration::ZERO;
             self.state = KurvePhase::StartCountdown {
                 started: Instant::now(),
             };
@@ -506,17 +811,34 @@ impl Kurve {
     }
 
     /// Tick the round countdown
-    fn tick_countdown(&mut self, ctx: &mut Context, started: Instant) {
+    fn tick_countdown(
+        &mut self,
+        ctx: &mut Context,
+        audio: &mut Audio,
+        started: Instant,
+    ) -> GameResult {
         for curve in self.curves.iter_mut() {
             curve.rotate(ctx);
         }
         let now = Instant::now();
-        if now.duration_since(started) >= WINNER_GLOAT_DURATION {
+        let elapsed = now.duration_since(started);
+
+        if elapsed >= WINNER_GLOAT_DURATION {
             for curve in self.curves.iter_mut() {
                 curve.trail_ts = Instant::now();
             }
+            self.round_time = Duration::ZERO;
             self.state = KurvePhase::Running;
+            return audio.play(ctx, SoundEffect::RoundStart);
+        }
+
+        // Tick whenever the displayed second changes, including the first frame
+        let previous = elapsed.saturating_sub(ctx.time.delta());
+        if countdown_second(elapsed) != countdown_second(previous) {
+            audio.play(ctx, SoundEffect::CountdownTick)?;
         }
+
+        Ok(())
     }
 
     /// Process the setup menu
@@ -571,10 +893,30 @@ impl Kurve {
                         }
                     }
                 }
+                KurveMenuItem::Wrap => {
+                    // Rules are fixed for the whole match
+                    if !self.paused() {
+                        self.rules.wrap = !self.rules.wrap;
+                    }
+                }
+                KurveMenuItem::SuddenDeath => {
+                    if !self.paused() {
+                        self.rules.cycle_shrink();
+                    }
+                }
+                KurveMenuItem::Map => {
+                    if !self.paused() {
+                        self.rules.cycle_map(&self.maps);
+                        self.set_setup_bounds(ctx.gfx.drawable_size());
+                        self.reset_setup_curves();
+                    }
+                }
                 KurveMenuItem::Start => {
                     self.set_running_bounds(ctx.gfx.drawable_size());
                     self.reset_curves();
                     self.powers.reset_powermods();
+                    self.round_time = Duration::ZERO;
+                    self.rules.seed = thread_rng().gen();
                     self.state = KurvePhase::StartCountdown {
                         started: Instant::now(),
                     };
@@ -602,34 +944,9 @@ impl Kurve {
 
     /// Process the setup stagin area
     fn tick_setup_curves(&mut self, ctx: &mut Context, delta: f32) {
-        // Calculate wall collisions
+        // Curves always wrap around the preview arena
         for curve in self.curves.iter_mut() {
-            let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
-            if let Some(collision) =
-                check_border_axis_collision(self.bounds.x_min, self.bounds.x_max, bbox.xs())
-            {
-                match collision {
-                    Collision::Min => {
-                        curve.position.x = self.bounds.x_max;
-                    }
-                    Collision::Max => {
-                        curve.position.x = self.bounds.x_min;
-                    }
-                }
-            }
-
-            if let Some(collision) =
-                check_border_axis_collision(self.bounds.y_min, self.bounds.y_max, bbox.ys())
-            {
-                match collision {
-                    Collision::Min => {
-                        curve.position.y = self.bounds.y_max;
-                    }
-                    Collision::Max => {
-                        curve.position.y = self.bounds.y_min;
-                    }
-                }
-            }
+            self.rules.map.shape.wrap(self.bounds, curve, delta);
 
             curve.rotate(ctx);
 
@@ -637,8 +954,8 @@ impl Kurve {
 
             curve.mv(delta);
 
-            if curve.lines.len() > 20 {
-                curve.lines.pop_front();
+            if curve.trail.len() > 20 {
+                curve.trail.pop_front();
             }
         }
     }
@@ -662,6 +979,13 @@ impl Kurve {
 
         self.add_player(player, curve);
 
+        if let Some(curve) = self.curves.last_mut() {
+            curve.position = self
+                .rules
+                .map
+                .random_pos(self.arena, SPAWN_OBSTACLE_DISTANCE);
+        }
+
         let mut idx = 0;
         let mut items = self.menu.items.iter();
 
@@ -697,17 +1021,65 @@ impl Kurve {
     /// Reset the curves' positions and liveness
     #[inline]
     fn reset_curves(&mut self) {
+        let mut spawns = self.rules.map.spawns.clone();
+        spawns.shuffle(&mut thread_rng());
+
         for curve in self.curves.iter_mut() {
-            curve.position = self.bounds.random_pos();
+            match spawns.pop() {
+                Some(spawn) => {
+                    curve.position = self.arena.place(spawn.point);
+                    curve.rotation = spawn.rotation;
+                }
+                None => {
+                    curve.position = self
+                        .rules
+                        .map
+                        .random_pos(self.arena, SPAWN_OBSTACLE_DISTANCE);
+                    curve.rotation = random_rot();
+                }
+            }
             curve.alive = true;
-            curve.rotation = random_rot();
-            curve.lines.clear();
+            curve.trail.clear();
             curve.trail_active = true;
             curve.trail_fuse = Curve::new_trail_fuse();
-            curve.velocity = DEFAULT_VELOCITY;
-            curve.girth = DEFAULT_GIRTH;
-            curve.rotation_speed = DEFAULT_ROTATION;
+            curve.portal_exit = None;
+            curve.shield_break = None;
+            curve.clear_effects();
+        }
+    }
+
+    /// Move the curves in the preview arena somewhere inside its current shape, away from
+    /// obstacles
+    #[inline]
+    fn reset_setup_curves(&mut self) {
+        for curve in self.curves.iter_mut() {
+            curve.position = self
+                .rules
+                .map
+                .random_pos(self.arena, SPAWN_OBSTACLE_DISTANCE);
+            curve.trail.clear();
+        }
+    }
+
+    /// Load the maps directory again to pick up maps saved in the editor. Keeps the chosen map
+    /// if it still exists. Only has an effect during setup.
+    pub fn reload_maps(&mut self, ctx: &mut Context) {
+        if !matches!(self.state, KurvePhase::Setup) {
+            return;
         }
+
+        let (maps, map_errors) = Map::load_all(ctx);
+
+        self.rules.map = maps
+            .iter()
+            .find(|map| map.name == self.rules.map.name)
+            .cloned()
+            .unwrap_or_default();
+        self.maps = maps;
+        self.map_errors = map_errors;
+
+        self.set_setup_bounds(ctx.gfx.drawable_size());
+        self.reset_setup_curves();
     }
 
     /// Check whether there is only one curve currently alive
@@ -754,21 +1126,27 @@ impl Kurve {
         matches!(self.state, KurvePhase::Paused)
     }
 
+    /// The preview keeps the proportions of the map while fitting in [SIZE_SMALL]
     #[inline]
     fn set_setup_bounds(&mut self, drawable_size: (f32, f32)) {
+        let (width, height) = self.rules.map.size;
+        let scale = (SIZE_SMALL.0 / width).min(SIZE_SMALL.1 / height).min(1.);
+
         self.bounds = ArenaBounds::new(
             Point2 {
                 x: drawable_size.0 * SETUP_KURVE_CENTER.0,
                 y: drawable_size.1 * SETUP_KURVE_CENTER.1,
             },
             drawable_size,
-            SIZE_SMALL,
-        )
+            (width * scale, height * scale),
+        );
+        self.arena = self.bounds;
     }
 
     #[inline]
     fn set_running_bounds(&mut self, drawable_size: (f32, f32)) {
-        self.bounds = ArenaBounds::new_center(drawable_size, SIZE_SMALL)
+        self.bounds = ArenaBounds::new_center(drawable_size, self.rules.map.size);
+        self.arena = self.bounds;
     }
 }
 
@@ -777,43 +1155,104 @@ impl Kurve {
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         // Draw arena
 
-        let arena_rect = graphics::Rect::new(
-            self.bounds.x_min,
-            self.bounds.y_min,
-            self.bounds.x_max - self.bounds.x_min,
-            self.bounds.y_max - self.bounds.y_min,
-        );
+        let outline = self.rules.map.shape.outline(self.bounds);
 
-        let arena_mesh = graphics::Mesh::new_rectangle(
+        let arena_mesh = graphics::Mesh::new_polygon(
             ctx,
             graphics::DrawMode::fill(),
-            arena_rect,
+            &outline,
             Color::from_rgb(30, 30, 30),
         )?;
 
         let draw_param = graphics::DrawParam::default();
         canvas.draw(&arena_mesh, draw_param);
 
-        // Draw curves
+        // Passable borders are dashed
 
-        for curve in self.curves.iter() {
-            // TODO: Resize arrs
-            let (mut arrs, meshes) = Line::line_meshes_and_arrays(ctx, curve.color)?;
+        let passable = self.rules.wrap
+            || self
+                .curves
+                .iter()
+                .any(|curve| curve.has_effect(Effect::WallPass));
 
-            for line in curve.lines.iter() {
-                for point in line.iter() {
-                    arrs[line.girth].push((*point).into());
-                }
+        if self.sudden_death() {
+            // The closing border is solid and deadly
+            let border = graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::stroke(2.),
+                &outline,
+                Color::from_rgb(220, 40, 40),
+            )?;
+            canvas.draw(&border, draw_param);
+        } else if passable {
+            let border = dashed_outline(ctx, &outline, Color::from_rgb(200, 200, 200))?;
+            canvas.draw(&border, draw_param);
+        }
+
+        // Draw obstacles under the trails
+
+        draw_obstacles(
+            ctx,
+            canvas,
+            &self.rules.map.obstacles,
+            self.arena,
+            OBSTACLE_COLOR,
+        )?;
+        draw_obstacles(
+            ctx,
+            canvas,
+            &self
+                .rules
+                .map
+                .hazards_at(self.round_time.as_secs_f32(), self.rules.seed),
+            self.arena,
+            HAZARD_COLOR,
+        )?;
+        draw_portals(ctx, canvas, &self.rules.map, self.arena)?;
+
+        // Draw curves
+
+        for (i, curve) in self.curves.iter().enumerate() {
+            draw_curve(ctx, canvas, curve)?;
+
+            // Shielded curves are wrapped in a bubble
+
+            if curve.alive && curve.has_effect(Effect::Shield) {
+                let bubble = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    curve.position,
+                    curve.girth.as_f32() + 3.,
+                    0.1,
+                    Color::new(0.6, 0.85, 1., 0.45),
+                )?;
+                canvas.draw(&bubble, draw_param);
             }
 
-            for (mesh, arr) in meshes.into_iter().zip(arrs) {
-                canvas.draw_instanced_mesh(mesh, &arr, draw_param);
+            // Timers of the active effects
+
+            if curve.alive {
+                self.draw_curve_effects(ctx, canvas, i, curve)?;
             }
 
-            canvas.draw(
-                &Curve::create_mesh(ctx, curve.color, curve.girth)?,
-                draw_param.dest(curve.position),
-            );
+            // Warn players about their reversed controls
+
+            let reversed = curve.alive && curve.reversed();
+
+            if let Some(img) = self.icons.get(Reverse.icon()).filter(|_| reversed) {
+                let scale = Reverse.icon_scale() * 0.5;
+                let offset = curve.girth.as_f32() + 6.;
+                canvas.draw(
+                    img,
+                    graphics::DrawParam::new()
+                        .scale(scale)
+                        .color(curve.color)
+                        .dest(Point2 {
+                            x: curve.position.x + offset,
+                            y: curve.position.y - offset - img.height() as f32 * scale.y,
+                        }),
+                );
+            }
 
             // Draw debug bbox
 
@@ -836,8 +1275,31 @@ impl Kurve {
 
         // Draw powermods
 
+        let now = Instant::now();
+
         for powermod in self.powers.powermods.values() {
             let pos = powermod.point;
+
+            // Announce the powermod with a ring closing in on the spot it will appear at
+
+            if !powermod.collectible(now) {
+                let progress = powermod.spawn_progress(now);
+                let mut color = powermod.color();
+                color.a = progress;
+
+                let ring = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.),
+                    pos,
+                    POWERMOD_SIZE * (3. - 2. * progress),
+                    0.1,
+                    color,
+                )?;
+                canvas.draw(&ring, draw_param);
+
+                continue;
+            }
+
             let poly = BoundingCircle::new(pos, POWERMOD_SIZE).0;
             let c_mesh = graphics::Mesh::new_polygon(
                 ctx,
@@ -848,66 +1310,51 @@ impl Kurve {
 
             canvas.draw(&c_mesh, draw_param);
 
-            let scale = glam::Vec2::new(0.07, 0.07);
-            let img = self.image_bank.get_powermod(powermod.ty);
+            // Powermods hitting other curves radiate a ring
+            if powermod.target != PowerTarget::Collector {
+                let ring = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.),
+                    pos,
+                    POWERMOD_SIZE + 4.,
+                    0.1,
+                    powermod.color(),
+                )?;
+                canvas.draw(&ring, draw_param);
+            }
+
+            let Some(img) = self.icons.get(powermod.ty.icon()) else {
+                continue;
+            };
+            let scale = powermod.ty.icon_scale();
             canvas.draw(
                 img,
                 graphics::DrawParam::new().scale(scale).dest(Point2 {
-                    x: pos.x - img.width() as f32 * 0.5 * 0.07,
-                    y: pos.y - img.height() as f32 * 0.5 * 0.07 - 2.,
+                    x: pos.x - img.width() as f32 * 0.5 * scale.x,
+                    y: pos.y - img.height() as f32 * 0.5 * scale.y - 2.,
                 }),
             );
 
             // Draw debug bbox
         }
 
-        // Draw debug power timeouts
-
-        for (i, timeout) in self.powers.power_timeouts.iter().enumerate() {
-            let mut text = graphics::Text::new(
-                Instant::now()
-                    .duration_since(timeout.started)
-                    .as_millis()
-                    .to_string(),
-            );
-            text.set_scale(PxScale::from(24.));
-
-            canvas.draw(
-                &text,
-                DrawParam::default().dest(Point2 {
-                    x: 100.,
-                    y: 20. * i as f32,
-                }),
-            );
-
-            let mut text = graphics::Text::new(timeout.ty.to_string());
-            text.set_scale(PxScale::from(24.));
-
-            canvas.draw(
-                &text,
-                DrawParam::default().dest(Point2 {
-                    x: 10.,
-                    y: 44. * i as f32,
-                }),
-            );
-        }
-
         match self.state {
             KurvePhase::Setup => {
-                self.menu.draw(ctx, canvas, self.paused())?;
+                self.menu.draw(ctx, canvas, self.paused(), &self.rules)?;
+                self.draw_map_errors(ctx, canvas);
                 return Ok(());
             }
             KurvePhase::StartCountdown { started } => {
                 self.draw_countdown_phase(ctx, canvas, started)?
             }
-            KurvePhase::Paused => self.menu.draw(ctx, canvas, self.paused())?,
+            KurvePhase::Paused => self.menu.draw(ctx, canvas, self.paused(), &self.rules)?,
             KurvePhase::Winner { id, .. } => {
                 self.draw_winner_phase(ctx, canvas, &self.players[id].name)
             }
             KurvePhase::Running => {}
         }
 
-        self.draw_score(ctx, canvas);
+        self.draw_score(ctx, canvas)?;
 
         Ok(())
     }
@@ -922,11 +1369,10 @@ impl Kurve {
         let (x, y) = ctx.gfx.drawable_size();
 
         // Draw the countdown
-        let second = (WINNER_GLOAT_DURATION.saturating_sub(Instant::now().duration_since(started)))
-            .as_secs()
-            + 1;
+        let second = countdown_second(Instant::now().duration_since(started));
 
         let mut text = graphics::Text::new(second.to_string());
+        text.set_font(self.assets.bold);
         text.set_scale(PxScale::from(24.));
 
         let rect = text.dimensions(ctx).unwrap();
@@ -970,10 +1416,32 @@ impl Kurve {
         Ok(())
     }
 
+    /// List the map files that failed to load at the bottom of the screen
+    fn draw_map_errors(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let (_, y) = ctx.gfx.drawable_size();
+
+        for (i, error) in self.map_errors.iter().rev().enumerate() {
+            let mut text = graphics::Text::new(error.to_string());
+            text.set_scale(PxScale::from(14.));
+            text.fragments_mut()
+                .iter_mut()
+                .for_each(|frag| frag.color = Some(Color::from_rgb(220, 80, 80)));
+
+            canvas.draw(
+                &text,
+                DrawParam::default().dest(Point2 {
+                    x: 10.,
+                    y: y - 24. - i as f32 * 18.,
+                }),
+            );
+        }
+    }
+
     fn draw_winner_phase(&self, ctx: &mut Context, canvas: &mut Canvas, player_name: &str) {
         let (x, y) = ctx.gfx.drawable_size();
 
         let mut text = graphics::Text::new(format!("{player_name} wins!"));
+        text.set_font(self.assets.bold);
         text.set_scale(PxScale::from(24.));
 
         let rect = text.dimensions(ctx).unwrap();
@@ -987,24 +1455,141 @@ impl Kurve {
         );
     }
 
-    fn draw_score(&self, ctx: &mut Context, canvas: &mut Canvas) {
+    /// Draw every player's score next to the icons of the effects active on their curve
+    fn draw_score(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         let (x, _) = ctx.gfx.drawable_size();
-        let mut score_text = String::new();
+        let now = Instant::now();
+
+        let rows = self
+            .players
+            .iter()
+            .map(|player| graphics::Text::new(format!("{}: {}", player.name, player.score)))
+            .collect::<Vec<_>>();
+
+        // Align the rows to the widest one, the icons extend to the right
+        let width = rows
+            .iter()
+            .map(|text| text.dimensions(ctx).unwrap().w)
+            .fold(0., f32::max);
+
+        let left = x * 0.5 - width * 0.5;
 
-        for player in self.players.iter() {
-            writeln!(score_text, "{}: {}", player.name, player.score).unwrap();
+        for (i, text) in rows.iter().enumerate() {
+            let y = 30. + i as f32 * HUD_ROW_HEIGHT;
+            let rect = text.dimensions(ctx).unwrap();
+
+            canvas.draw(
+                text,
+                DrawParam::default().dest(Point2 {
+                    x: left,
+                    y: y + HUD_ROW_HEIGHT * 0.5 - rect.h * 0.5,
+                }),
+            );
+
+            let mut icons_end = left + width;
+
+            for (j, timeout) in self.active_timeouts(i).enumerate() {
+                let center = Point2 {
+                    x: left + width + HUD_ICON_RADIUS * 2. + j as f32 * HUD_ICON_RADIUS * 2.6,
+                    y: y + HUD_ROW_HEIGHT * 0.5,
+                };
+                icons_end = center.x + HUD_ICON_RADIUS;
+
+                let background = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    center,
+                    HUD_ICON_RADIUS,
+                    0.1,
+                    timeout.target.color(),
+                )?;
+                canvas.draw(&background, DrawParam::default());
+
+                if let Some(img) = self.icons.get(timeout.ty.icon()) {
+                    let scale = timeout.ty.icon_scale();
+                    let scale = scale * (HUD_ICON_RADIUS * 1.5 / (img.width() as f32 * scale.x));
+                    canvas.draw(
+                        img,
+                        DrawParam::new().scale(scale).dest(Point2 {
+                            x: center.x - img.width() as f32 * 0.5 * scale.x,
+                            y: center.y - img.height() as f32 * 0.5 * scale.y,
+                        }),
+                    );
+                }
+
+                if let Some(timer) = radial_timer(
+                    ctx,
+                    center,
+                    HUD_ICON_RADIUS + 2.,
+                    timeout.remaining(now),
+                    Color::WHITE,
+                )? {
+                    canvas.draw(&timer, DrawParam::default());
+                }
+            }
+
+            // Announce fresh pickups by name
+
+            let newest = self
+                .active_timeouts(i)
+                .filter(|timeout| now.duration_since(timeout.started) < HUD_ANNOUNCE_DURATION)
+                .max_by_key(|timeout| timeout.started);
+
+            if let Some(timeout) = newest {
+                let mut text = graphics::Text::new(timeout.ty.name());
+                text.set_scale(PxScale::from(14.));
+                text.fragments_mut()
+                    .iter_mut()
+                    .for_each(|frag| frag.color = Some(timeout.target.color()));
+                let rect = text.dimensions(ctx).unwrap();
+
+                canvas.draw(
+                    &text,
+                    DrawParam::default().dest(Point2 {
+                        x: icons_end + HUD_ICON_RADIUS,
+                        y: y + HUD_ROW_HEIGHT * 0.5 - rect.h * 0.5,
+                    }),
+                );
+            }
         }
 
-        let score_text = graphics::Text::new(score_text);
+        Ok(())
+    }
+
+    /// Draw a ring of shrinking timers around the curve's head, one for each active effect
+    fn draw_curve_effects(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        index: usize,
+        curve: &Curve,
+    ) -> GameResult {
+        let now = Instant::now();
 
-        let score_rect = score_text.dimensions(ctx).unwrap();
+        for (i, timeout) in self.active_timeouts(index).enumerate() {
+            let radius = curve.girth.as_f32() + 5. + i as f32 * 3.;
 
-        let draw_param = DrawParam::default().dest(Point2 {
-            x: x * 0.5 - score_rect.w * 0.5,
-            y: 30.0,
-        });
+            if let Some(timer) = radial_timer(
+                ctx,
+                curve.position,
+                radius,
+                timeout.remaining(now),
+                timeout.target.color(),
+            )? {
+                canvas.draw(&timer, DrawParam::default());
+            }
+        }
+
+        Ok(())
+    }
 
-        canvas.draw(&score_text, draw_param);
+    /// Timeouts of the effects currently shown on the curve at `index`. Instant effects are
+    /// over as soon as they are applied so they are left out.
+    fn active_timeouts(&self, index: usize) -> impl Iterator<Item = &PowerTimeout> {
+        self.powers
+            .power_timeouts
+            .iter()
+            .filter(move |timeout| timeout.curve == index && !timeout.ty.duration().is_zero())
     }
 }
 
@@ -1057,10 +1642,221 @@ impl ArenaBounds {
         }
     }
 
+    /// The center of the arena and the radius of the largest circle fitting inside it
+    #[inline]
+    pub fn inscribed(&self) -> (Point2<f32>, f32) {
+        let center = Point2 {
+            x: (self.x_min + self.x_max) * 0.5,
+            y: (self.y_min + self.y_max) * 0.5,
+        };
+        let radius = (self.x_max - self.x_min).min(self.y_max - self.y_min) * 0.5;
+        (center, radius)
+    }
+
+    /// Convert a point in arena space to screen coordinates. Arena space spans `-1.0..=1.0`
+    /// on both axes across the [inscribed](Self::inscribed) circle, so maps keep their aspect
+    /// ratio whatever the size of the bounds.
+    #[inline]
+    pub fn place(&self, point: Point2<f32>) -> Point2<f32> {
+        let (center, radius) = self.inscribed();
+        Point2 {
+            x: center.x + point.x * radius,
+            y: center.y + point.y * radius,
+        }
+    }
+
     /// Return a random point within this arena's bounds
     pub fn random_pos(&self) -> Point2<f32> {
         random_pos((self.x_min, self.x_max), (self.y_min, self.y_max))
     }
+
+    /// Move every border `amount` towards the center, keeping at least `min_size` of the
+    /// original width and height.
+    pub fn shrink(&self, amount: f32, (min_x, min_y): (f32, f32)) -> Self {
+        let (width, height) = (self.x_max - self.x_min, self.y_max - self.y_min);
+        let shrink_x = amount.min(width * (1. - min_x) * 0.5);
+        let shrink_y = amount.min(height * (1. - min_y) * 0.5);
+
+        Self {
+            x_min: self.x_min + shrink_x,
+            x_max: self.x_max - shrink_x,
+            y_min: self.y_min + shrink_y,
+            y_max: self.y_max - shrink_y,
+        }
+    }
+
+    /// If the curve's next position is past a border, move the curve to the opposite border.
+    /// The curve is placed far enough from the border that its bounding box stays inside the
+    /// arena, so it does not get caught by border collisions. Since the trail is interpolated
+    /// from the new position no line is drawn across the arena.
+    pub fn wrap(&self, curve: &mut Curve, delta: f32) {
+        let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+        let margin = curve.girth.as_f32() + 1.;
+
+        if let Some(collision) = check_border_axis_collision(self.x_min, self.x_max, bbox.xs()) {
+            match collision {
+                Collision::Min => {
+                    curve.position.x = self.x_max - margin;
+                }
+                Collision::Max => {
+                    curve.position.x = self.x_min + margin;
+                }
+            }
+        }
+
+        if let Some(collision) = check_border_axis_collision(self.y_min, self.y_max, bbox.ys()) {
+            match collision {
+                Collision::Min => {
+                    curve.position.y = self.y_max - margin;
+                }
+                Collision::Max => {
+                    curve.position.y = self.y_min + margin;
+                }
+            }
+        }
+    }
+
+    /// If the curve's next position is past a border, turn the curve away from it as if it
+    /// bounced off the border.
+    pub fn bounce(&self, curve: &mut Curve, delta: f32) {
+        let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+
+        if check_border_axis_collision(self.x_min, self.x_max, bbox.xs()).is_some() {
+            curve.rotation = PI - curve.rotation;
+        }
+
+        if check_border_axis_collision(self.y_min, self.y_max, bbox.ys()).is_some() {
+            curve.rotation = -curve.rotation;
+        }
+    }
+}
+
+/// Options affecting a whole match
+#[derive(Debug, Default, Clone)]
+pub struct Rules {
+    /// Curves pass through the arena borders and come out on the opposite side
+    pub wrap: bool,
+
+    /// How long a round runs before the arena starts shrinking, if ever
+    pub shrink_after: Option<Duration>,
+
+    /// The layout of the arena
+    pub map: Map,
+
+    /// Where the map's hazards start their cycles, picked when the match starts
+    pub seed: u64,
+}
+
+impl Rules {
+    /// Whether the curve passes through borders instead of colliding with them
+    #[inline]
+    pub fn wraps(&self, curve: &Curve) -> bool {
+        self.wrap || curve.has_effect(Effect::WallPass)
+    }
+
+    /// Switch to the next sudden death option
+    pub fn cycle_shrink(&mut self) {
+        let current = SHRINK_OPTIONS
+            .iter()
+            .position(|option| *option == self.shrink_after)
+            .unwrap_or(0);
+        self.shrink_after = SHRINK_OPTIONS[(current + 1) % SHRINK_OPTIONS.len()];
+    }
+
+    /// Switch to the next of the available maps
+    pub fn cycle_map(&mut self, maps: &[Map]) {
+        let current = maps.iter().position(|map| *map == self.map).unwrap_or(0);
+        if let Some(map) = maps.get((current + 1) % maps.len()) {
+            self.map = map.clone();
+        }
+    }
+
+    #[inline]
+    pub fn arena_mode(&self) -> ArenaMode {
+        if self.wrap {
+            ArenaMode::Wrap
+        } else {
+            ArenaMode::Solid
+        }
+    }
+}
+
+/// How the arena borders behave during a match. Powermods can be restricted to certain modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaMode {
+    /// Curves die when hitting the borders
+    Solid,
+
+    /// Curves come out on the opposite side of the borders
+    Wrap,
+}
+
+/// What a curve ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hit {
+    /// The border of the arena
+    Border,
+
+    /// An obstacle, a hazard or a trail
+    Solid,
+}
+
+/// Check what the curve at `index` runs into with its bounding box at `bbox`. The border,
+/// obstacles and hazards are always solid, while gaps and Invulnerability only let the curve
+/// pass through trails.
+fn detect_collision(
+    shape: &ArenaShape,
+    bounds: ArenaBounds,
+    obstacles: &[Line],
+    curves: &[Curve],
+    index: usize,
+    bbox: BoundingBox,
+    now: Instant,
+) -> Option<Hit> {
+    if shape.escapes(bounds, bbox) {
+        return Some(Hit::Border);
+    }
+
+    if obstacles.iter().any(|line| check_bbox_colision(bbox, line)) {
+        return Some(Hit::Solid);
+    }
+
+    let curve = &curves[index];
+
+    // Gaps and Invulnerability only let the curve through trails
+    if !curve.trail_active {
+        return None;
+    }
+
+    // Other curves may have just come out of the same portal and left their trail right at
+    // the exit
+    if curve.in_portal_grace(now) || curve.in_shield_grace(now) {
+        return None;
+    }
+
+    for (j, other) in curves.iter().enumerate() {
+        let lines = &other.trail;
+
+        // Skip the last few lines of the current curve due to self collision
+        let line_count = if index == j {
+            lines
+                .len()
+                .saturating_sub(15 * (other.girth.as_f32() as usize).saturating_sub(1))
+        } else {
+            lines.len()
+        };
+
+        // Check for line collisions
+        if lines
+            .iter()
+            .take(line_count)
+            .any(|line| check_bbox_colision(bbox, line))
+        {
+            return Some(Hit::Solid);
+        }
+    }
+
+    None
 }
 
 #[inline]
@@ -1084,23 +1880,6 @@ pub fn check_bbox_colision(bbox: BoundingBox, line: &Line) -> bool {
     false
 }
 
-#[inline]
-pub fn check_border_collision(
-    x_min: f32,
-    x_max: f32,
-    y_min: f32,
-    y_max: f32,
-    bbox: BoundingBox,
-) -> bool {
-    for point in bbox {
-        if point.x < x_min || point.x > x_max || point.y < y_min || point.y > y_max {
-            return true;
-        }
-    }
-
-    false
-}
-
 enum Collision {
     Min,
     Max,
@@ -1121,6 +1900,190 @@ fn check_border_axis_collision(min: f32, max: f32, bbox: [f32; 9]) -> Option<Col
     None
 }
 
+/// Draw the obstacles in the color, filling the closed ones
+fn draw_obstacles(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    obstacles: &[Obstacle],
+    bounds: ArenaBounds,
+    color: Color,
+) -> GameResult {
+    for obstacle in obstacles.iter() {
+        if obstacle.closed() {
+            let fill = graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::fill(),
+                &obstacle.outline(bounds),
+                color,
+            )?;
+            canvas.draw(&fill, DrawParam::default());
+        }
+    }
+
+    let (mut arrs, meshes) = Line::line_meshes_and_arrays(ctx, color)?;
+
+    for line in obstacles.iter().flat_map(|obstacle| obstacle.lines(bounds)) {
+        for point in line.iter() {
+            arrs[OBSTACLE_GIRTH].push((*point).into());
+        }
+    }
+
+    for (mesh, arr) in meshes.into_iter().zip(arrs) {
+        canvas.draw_instanced_mesh(mesh, &arr, DrawParam::default());
+    }
+
+    Ok(())
+}
+
+/// Draw both ends of every portal as a ring with a notch pointing the way it faces
+fn draw_portals(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    map: &Map,
+    bounds: ArenaBounds,
+) -> GameResult {
+    for (i, portal) in map.portals.iter().enumerate() {
+        let color = Portal::color(i);
+        let radius = portal.screen_radius(bounds);
+
+        for end in portal.ends.iter() {
+            let center = bounds.place(end.center);
+
+            let ring = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::stroke(3.),
+                center,
+                radius,
+                0.5,
+                color,
+            )?;
+
+            let (cos, sin) = (end.rotation.cos(), end.rotation.sin());
+            let notch = graphics::Mesh::new_line(
+                ctx,
+                &[
+                    Point2 {
+                        x: center.x + (radius - 4.) * cos,
+                        y: center.y + (radius - 4.) * sin,
+                    },
+                    Point2 {
+                        x: center.x + (radius + 6.) * cos,
+                        y: center.y + (radius + 6.) * sin,
+                    },
+                ],
+                3.,
+                color,
+            )?;
+
+            canvas.draw(&ring, DrawParam::default());
+            canvas.draw(&notch, DrawParam::default());
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw the curve's trail and head
+fn draw_curve(ctx: &mut Context, canvas: &mut Canvas, curve: &Curve) -> GameResult {
+    // TODO: Resize arrs
+    let (mut arrs, meshes) = Line::line_meshes_and_arrays(ctx, curve.color)?;
+
+    for line in curve.trail.iter() {
+        for point in line.iter() {
+            arrs[line.girth].push((*point).into());
+        }
+    }
+
+    for (mesh, arr) in meshes.into_iter().zip(arrs) {
+        canvas.draw_instanced_mesh(mesh, &arr, DrawParam::default());
+    }
+
+    canvas.draw(
+        &Curve::create_mesh(ctx, curve.color, curve.girth)?,
+        DrawParam::default().dest(curve.position),
+    );
+
+    Ok(())
+}
+
+/// Build an arc around `center` starting at the top and going clockwise, covering `fraction` of
+/// the full circle. Returns `None` when there is nothing left to draw.
+fn radial_timer(
+    ctx: &mut Context,
+    center: Point2<f32>,
+    radius: f32,
+    fraction: f32,
+    color: Color,
+) -> GameResult<Option<graphics::Mesh>> {
+    const SEGMENTS: f32 = 32.;
+
+    let sweep = 2. * PI * fraction.clamp(0., 1.);
+    let segments = (SEGMENTS * fraction).ceil() as usize;
+
+    if segments == 0 {
+        return Ok(None);
+    }
+
+    let points = (0..=segments)
+        .map(|i| {
+            let angle = -FRAC_PI_2 + sweep * i as f32 / segments as f32;
+            Point2 {
+                x: center.x + radius * angle.cos(),
+                y: center.y + radius * angle.sin(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mesh = graphics::Mesh::new_line(ctx, &points, 2., color)?;
+
+    Ok(Some(mesh))
+}
+
+/// Build the closed outline going through the corners out of short dashes
+fn dashed_outline(
+    ctx: &mut Context,
+    corners: &[Point2<f32>],
+    color: Color,
+) -> GameResult<graphics::Mesh> {
+    const DASH: f32 = 10.;
+    const GAP: f32 = 8.;
+
+    let mut builder = graphics::MeshBuilder::new();
+
+    for (i, start) in corners.iter().enumerate() {
+        let end = corners[(i + 1) % corners.len()];
+        let (d_x, d_y) = (end.x - start.x, end.y - start.y);
+        let len = (d_x * d_x + d_y * d_y).sqrt();
+
+        let mut travelled = 0.;
+        while travelled < len {
+            let to = (travelled + DASH).min(len);
+            builder.line(
+                &[
+                    Point2 {
+                        x: start.x + d_x * travelled / len,
+                        y: start.y + d_y * travelled / len,
+                    },
+                    Point2 {
+                        x: start.x + d_x * to / len,
+                        y: start.y + d_y * to / len,
+                    },
+                ],
+                2.,
+                color,
+            )?;
+            travelled += DASH + GAP;
+        }
+    }
+
+    Ok(graphics::Mesh::from_data(ctx, builder.build()))
+}
+
+#[inline]
+fn distance(a: Point2<f32>, b: Point2<f32>) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
 #[inline]
 fn random_pos<T>(bounds_x: (T, T), bounds_y: (T, T)) -> Point2<T>
 where
@@ -1132,7 +2095,142 @@ where
     }
 }
 
+/// The second displayed during the countdown after `elapsed` time has passed
+#[inline]
+fn countdown_second(elapsed: Duration) -> u64 {
+    WINNER_GLOAT_DURATION.saturating_sub(elapsed).as_secs() + 1
+}
+
 #[inline]
 fn random_rot() -> f32 {
     rand::thread_rng().gen_range(0f32..2. * PI)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hazard::Hazard;
+
+    const BOUNDS: ArenaBounds = ArenaBounds {
+        x_min: 0.,
+        x_max: 200.,
+        y_min: 0.,
+        y_max: 200.,
+    };
+
+    /// A curve in the middle of the arena with its trail gapped
+    fn gapped() -> Curve {
+        let mut curve = Curve::at(Point2 { x: 100., y: 100. }, 0.);
+        curve.trail_active = false;
+        curve
+    }
+
+    /// Another curve whose trail runs straight through the middle of the arena
+    fn crossing() -> Curve {
+        let mut curve = Curve::at(Point2 { x: 100., y: 50. }, 0.);
+        curve.trail.push(Line::interpolate(
+            Point2 { x: 100., y: 50. },
+            Point2 { x: 100., y: 150. },
+            curve.girth,
+        ));
+        curve
+    }
+
+    fn collide(obstacles: &[Line], curves: &[Curve]) -> Option<Hit> {
+        let bbox = BoundingBox::new(curves[0].position, curves[0].girth.as_f32());
+
+        detect_collision(
+            &ArenaShape::Rectangle,
+            BOUNDS,
+            obstacles,
+            curves,
+            0,
+            bbox,
+            Instant::now(),
+        )
+    }
+
+    #[test]
+    fn gapped_curve_dies_on_a_wall() {
+        let wall = Obstacle::Wall {
+            from: Point2 { x: 0., y: -0.5 },
+            to: Point2 { x: 0., y: 0.5 },
+        };
+
+        assert_eq!(collide(&wall.lines(BOUNDS), &[gapped()]), Some(Hit::Solid));
+    }
+
+    #[test]
+    fn gapped_curve_dies_on_a_hazard() {
+        // Spins around the middle of the arena, where the curve is
+        let map = Map {
+            hazards: vec![Hazard::Sweeper {
+                pivot: Point2 { x: 0., y: 0. },
+                length: 0.5,
+                speed: 1.,
+            }],
+            ..Map::default()
+        };
+
+        for seed in 0..8 {
+            let hazards = map.hazard_lines(BOUNDS, 1.5, seed);
+            assert_eq!(
+                collide(&hazards, &[gapped()]),
+                Some(Hit::Solid),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn gapped_curve_passes_through_trails() {
+        assert_eq!(collide(&[], &[gapped(), crossing()]), None);
+        assert_eq!(
+            collide(
+                &[],
+                &[Curve::at(Point2 { x: 100., y: 100. }, 0.), crossing()]
+            ),
+            Some(Hit::Solid)
+        );
+    }
+
+    #[test]
+    fn shield_grace_only_passes_through_trails() {
+        let shielded = || {
+            let mut curve = Curve::at(Point2 { x: 100., y: 100. }, 0.);
+            curve.shield_break = Some(Instant::now());
+            curve
+        };
+
+        let wall = Obstacle::Wall {
+            from: Point2 { x: 0., y: -0.5 },
+            to: Point2 { x: 0., y: 0.5 },
+        };
+
+        // Unlike a gap, the curve keeps drawing its trail
+        assert!(shielded().trail_active);
+        assert_eq!(collide(&[], &[shielded(), crossing()]), None);
+        assert_eq!(
+            collide(&wall.lines(BOUNDS), &[shielded()]),
+            Some(Hit::Solid)
+        );
+    }
+
+    #[test]
+    fn gapped_curve_dies_on_the_shrinking_border() {
+        let shrunk = BOUNDS.shrink(40., SHRINK_MIN_SIZE);
+
+        let mut curve = Curve::at(Point2 { x: 30., y: 100. }, PI);
+        curve.trail_active = false;
+        let bbox = BoundingBox::new(curve.position, curve.girth.as_f32());
+        let curves = [curve];
+
+        for shape in [ArenaShape::Rectangle, ArenaShape::Circle] {
+            let hit =
+                |bounds| detect_collision(&shape, bounds, &[], &curves, 0, bbox, Instant::now());
+
+            assert_eq!(hit(BOUNDS), None, "{shape:?}");
+            assert_eq!(hit(shrunk), Some(Hit::Border), "{shape:?}");
+        }
+    }
+}
@@ -1,10 +1,14 @@
-use std::collections::VecDeque;
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI};
 use std::fmt::{Debug, Display};
 use std::time::{Duration, Instant};
 
 use super::point::{Girth, Line};
-use super::{DEFAULT_GIRTH, DEFAULT_ROTATION, INV_DURATION, TRAIL_SKIP_MAX, TRAIL_SKIP_MIN};
+use super::portal::PORTAL_GRACE;
+use super::trail::Trail;
+use super::{
+    DEFAULT_GIRTH, DEFAULT_ROTATION, DEFAULT_VELOCITY, INV_DURATION, MAX_ROTATION, MAX_VELOCITY,
+    MIN_ROTATION, MIN_VELOCITY, SHIELD_GRACE, TRAIL_SKIP_MAX, TRAIL_SKIP_MIN,
+};
 use crate::display_key;
 use crate::kurve::ArenaBounds;
 use ggez::graphics::Color;
@@ -23,15 +27,32 @@ pub struct Curve {
     /// Rotation angle in rad
     pub rotation: f32,
 
-    /// How fast the curve is moving
+    /// How fast the curve is moving. Computed from the base stats and active effects.
     pub velocity: f32,
 
-    /// How much to increment rotation on movement
+    /// How much to increment rotation on movement. Computed from the base stats and active
+    /// effects.
     pub rotation_speed: f32,
 
-    /// Used for multiplying the bounding box distance
+    /// Used for multiplying the bounding box distance. Computed from the base stats and active
+    /// effects.
     pub girth: Girth,
 
+    /// Stats of the curve without any effects
+    pub base: BaseStats,
+
+    /// Effects of the powermods currently affecting the curve
+    pub effects: Vec<Effect>,
+
+    /// When the curve got frozen, used to delay the trail timers by the time spent frozen
+    pub frozen_at: Option<Instant>,
+
+    /// When the curve last came out of a portal, see [PORTAL_GRACE]
+    pub portal_exit: Option<Instant>,
+
+    /// When the curve's shield last absorbed a collision, see [SHIELD_GRACE]
+    pub shield_break: Option<Instant>,
+
     /// The movement keycodes for this curve
     pub move_keys: MoveKeys,
 
@@ -46,7 +67,7 @@ pub struct Curve {
     pub trail_active: bool,
 
     /// The curves for game logic
-    pub lines: VecDeque<Line>,
+    pub trail: Trail,
 
     pub alive: bool,
 
@@ -60,6 +81,12 @@ impl Debug for Curve {
             .field("position", &self.position)
             .field("rotation", &self.rotation)
             .field("velocity", &self.velocity)
+            .field("rotation_speed", &self.rotation_speed)
+            .field("girth", &self.girth)
+            .field("effects", &self.effects)
+            .field("frozen_at", &self.frozen_at)
+            .field("portal_exit", &self.portal_exit)
+            .field("shield_break", &self.shield_break)
             .field("move_keys", &self.move_keys)
             .field("trail_fuse", &self.trail_fuse)
             .field("trail_ts", &self.trail_ts)
@@ -91,10 +118,15 @@ impl Curve {
             velocity,
             rotation_speed: DEFAULT_ROTATION,
             girth: DEFAULT_GIRTH,
+            base: BaseStats::default(),
+            effects: vec![],
+            frozen_at: None,
+            portal_exit: None,
+            shield_break: None,
 
             move_keys: mv_keys,
             player_id,
-            lines: VecDeque::new(),
+            trail: Trail::default(),
 
             trail_fuse: Self::new_trail_fuse(),
             trail_ts: std::time::Instant::now(),
@@ -113,7 +145,7 @@ impl Curve {
             velocity: VELOCITY,
             move_keys: mv_keys,
             player_id,
-            lines: VecDeque::new(),
+            trail: Trail::default(),
 
             trail_fuse: new_trail_fuse(),
             trail_ts: std::time::Instant::now(),
@@ -126,15 +158,171 @@ impl Curve {
     /// Checks whether a move key is pressed and rotates the curve accordingly
     #[inline]
     pub fn rotate(&mut self, ctx: &mut Context) {
-        if ctx.keyboard.is_key_pressed(self.move_keys.cw) {
+        if self.frozen() {
+            return;
+        }
+
+        if self.has_effect(Effect::RightAngle) {
+            self.snap_rotate(ctx);
+            return;
+        }
+
+        let keys = self.controls();
+
+        if ctx.keyboard.is_key_pressed(keys.cw) {
             self.rotation += self.rotation_speed;
         }
 
-        if ctx.keyboard.is_key_pressed(self.move_keys.ccw) {
+        if ctx.keyboard.is_key_pressed(keys.ccw) {
             self.rotation -= self.rotation_speed;
         }
     }
 
+    /// Turns the curve by a right angle once per key press. Holding the key does nothing.
+    /// The turn happens before the trail for this tick is interpolated, so the trail gets
+    /// a sharp corner at the current position.
+    #[inline]
+    fn snap_rotate(&mut self, ctx: &mut Context) {
+        let keys = self.controls();
+
+        if ctx.keyboard.is_key_just_pressed(keys.cw) {
+            self.rotation += FRAC_PI_2;
+        }
+
+        if ctx.keyboard.is_key_just_pressed(keys.ccw) {
+            self.rotation -= FRAC_PI_2;
+        }
+    }
+
+    /// Whether the curve currently turns the opposite way of its keys
+    #[inline]
+    pub fn reversed(&self) -> bool {
+        self.effect_count(Effect::Reverse) % 2 == 1
+    }
+
+    /// The keys currently steering the curve, with the directions swapped while reversed
+    #[inline]
+    pub fn controls(&self) -> MoveKeys {
+        if self.reversed() {
+            MoveKeys {
+                cw: self.move_keys.ccw,
+                ccw: self.move_keys.cw,
+            }
+        } else {
+            self.move_keys
+        }
+    }
+
+    /// Start applying the effect to the curve
+    pub fn add_effect(&mut self, effect: Effect) {
+        self.effects.push(effect);
+        self.recompute_stats();
+    }
+
+    /// Stop applying a single instance of the effect. Does nothing if the effect is not active.
+    pub fn remove_effect(&mut self, effect: Effect) {
+        if let Some(i) = self.effects.iter().position(|e| *e == effect) {
+            self.effects.remove(i);
+            self.recompute_stats();
+        }
+    }
+
+    #[inline]
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        self.effects.contains(&effect)
+    }
+
+    /// How many instances of the effect are active
+    #[inline]
+    pub fn effect_count(&self, effect: Effect) -> usize {
+        self.effects.iter().filter(|e| **e == effect).count()
+    }
+
+    /// Stop the curve in its tracks. Freezes stack, the curve moves again once every one of them
+    /// is thawed.
+    pub fn freeze(&mut self) {
+        if !self.frozen() {
+            self.frozen_at = Some(Instant::now());
+        }
+        self.add_effect(Effect::Freeze);
+    }
+
+    /// Undo a single [freeze][Self::freeze]. When the last one is gone the trail timers are
+    /// pushed back by the time spent frozen, so the curve continues its trail or gap exactly
+    /// where it stopped instead of getting a free gap.
+    pub fn thaw(&mut self) {
+        self.remove_effect(Effect::Freeze);
+
+        if self.frozen() {
+            return;
+        }
+
+        if let Some(frozen_at) = self.frozen_at.take() {
+            self.trail_ts += Instant::now().duration_since(frozen_at);
+        }
+    }
+
+    /// Stop drawing the trail for a moment. The curve passes through trails while it leaves
+    /// none of its own.
+    #[inline]
+    pub fn skip_trail(&mut self) {
+        self.trail_active = false;
+        self.trail_ts = Instant::now();
+    }
+
+    #[inline]
+    pub fn frozen(&self) -> bool {
+        self.has_effect(Effect::Freeze)
+    }
+
+    /// Whether the curve came out of a portal just now and should not collide with trails yet
+    #[inline]
+    pub fn in_portal_grace(&self, now: Instant) -> bool {
+        self.portal_exit
+            .is_some_and(|exit| now.duration_since(exit) < PORTAL_GRACE)
+    }
+
+    /// Whether the curve's shield absorbed a collision just now and it should not collide with
+    /// trails yet
+    #[inline]
+    pub fn in_shield_grace(&self, now: Instant) -> bool {
+        self.shield_break
+            .is_some_and(|shield_break| now.duration_since(shield_break) < SHIELD_GRACE)
+    }
+
+    /// Remove every effect and modification, leaving only the base stats
+    pub fn clear_effects(&mut self) {
+        self.effects.clear();
+        self.frozen_at = None;
+        self.recompute_stats();
+    }
+
+    /// Set the stats to the base stats modified by all the active effects. The effects are summed
+    /// before clamping so the order they were applied and removed in never matters.
+    pub fn recompute_stats(&mut self) {
+        let mut velocity = self.base.velocity;
+        let mut rotation_speed = self.base.rotation_speed;
+        let mut girth = 0;
+
+        for effect in self.effects.iter() {
+            match effect {
+                Effect::Velocity(v) => velocity += v,
+                Effect::RotationSpeed(r) => rotation_speed += r,
+                Effect::Girth(g) => girth += g,
+                _ => {}
+            }
+        }
+
+        // Dead and frozen curves stay in place
+        self.velocity = if self.alive && !self.frozen() {
+            velocity.clamp(MIN_VELOCITY, MAX_VELOCITY)
+        } else {
+            0.
+        };
+        self.rotation_speed = rotation_speed.clamp(MIN_ROTATION, MAX_ROTATION);
+        self.girth = self.base.girth.step(girth);
+    }
+
     #[inline]
     pub fn mv(&mut self, delta: f32) {
         self.position.x += self.velocity * delta * self.rotation.cos();
@@ -167,6 +355,11 @@ impl Curve {
             return;
         }
 
+        // Trail timers are on hold until the curve thaws
+        if self.frozen() {
+            return;
+        }
+
         let now = std::time::Instant::now();
 
         // Disable trail if countdown is done and invulnerability countdown
@@ -185,7 +378,7 @@ impl Curve {
         if self.trail_active {
             // Push the line to the actual self
             let line = Line::interpolate(self.position, self.next_pos(delta), self.girth);
-            self.lines.push_back(line);
+            self.trail.push(line);
         }
     }
 
@@ -214,6 +407,85 @@ impl Curve {
     }
 }
 
+#[cfg(test)]
+impl Curve {
+    /// A living curve with the default stats and an active trail
+    pub fn at(position: Point2<f32>, rotation: f32) -> Self {
+        Self {
+            player_id: 0,
+            position,
+            rotation,
+            velocity: 100.,
+            rotation_speed: 0.,
+            girth: DEFAULT_GIRTH,
+            base: BaseStats::default(),
+            effects: vec![],
+            frozen_at: None,
+            portal_exit: None,
+            shield_break: None,
+            move_keys: MoveKeys::default(),
+            trail_fuse: Duration::MAX,
+            trail_ts: Instant::now(),
+            trail_active: true,
+            trail: Trail::default(),
+            alive: true,
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// The stats a curve starts every round with
+#[derive(Debug, Clone, Copy)]
+pub struct BaseStats {
+    pub velocity: f32,
+    pub rotation_speed: f32,
+    pub girth: Girth,
+}
+
+impl Default for BaseStats {
+    fn default() -> Self {
+        Self {
+            velocity: DEFAULT_VELOCITY,
+            rotation_speed: DEFAULT_ROTATION,
+            girth: DEFAULT_GIRTH,
+        }
+    }
+}
+
+/// A change to the curve, active until removed. Effects stack, every active instance counts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Added to the velocity
+    Velocity(f32),
+
+    /// Added to the rotation speed
+    RotationSpeed(f32),
+
+    /// Amount of girth steps to grow, or shrink when negative
+    Girth(i32),
+
+    /// The curve turns 90 degrees on every key press instead of steering freely
+    RightAngle,
+
+    /// The curve passes through the arena borders
+    WallPass,
+
+    /// The curve leaves no trail. The trail is only restored once the last one runs out.
+    Invulnerability,
+
+    /// The curve survives a fatal collision
+    Shield,
+
+    /// The curve stands still, cannot steer and leaves no trail, see [Curve::freeze]
+    Freeze,
+
+    /// Nearby powermods drift towards the curve, faster with every instance
+    Magnet,
+
+    /// The move keys are swapped, so the controls are only reversed when the count is odd
+    Reverse,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct MoveKeys {
     pub cw: KeyCode,
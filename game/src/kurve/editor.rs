@@ -0,0 +1,813 @@
+use super::{
+    check_bbox_colision,
+    curve::{Curve, MoveKeys},
+    distance, draw_curve, draw_obstacles, draw_portals,
+    map::{self, Map, Spawn, Zone},
+    obstacle::Obstacle,
+    point::BoundingBox,
+    portal::{Portal, PortalEnd},
+    powerup::PowerTarget,
+    shape::ArenaShape,
+    ArenaBounds, DEFAULT_VELOCITY, HAZARD_COLOR, OBSTACLE_COLOR, SPAWN_OBSTACLE_DISTANCE,
+};
+use crate::key_to_str;
+use ggez::{
+    graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale},
+    input::{keyboard::KeyCode, mouse::MouseButton},
+    mint::Point2,
+    Context, GameResult,
+};
+use std::time::{Duration, Instant};
+
+/// Distance between grid lines in arena space
+const GRID_STEP: f32 = 0.05;
+
+/// Radius of placed portals in arena space
+const PORTAL_RADIUS: f32 = 0.06;
+
+/// How long save and load results stay on screen
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+
+/// Keys steering the test drive curve
+const TEST_DRIVE_KEYS: MoveKeys = MoveKeys {
+    ccw: KeyCode::Left,
+    cw: KeyCode::Right,
+};
+
+const HELP: [&str; 12] = [
+    "1 border  2 wall  3 pillar  4 block  5 spawn  6 zone  7 portal",
+    "Left click  place",
+    "Right click  cancel / undo",
+    "Enter  close border polygon",
+    "R  rectangle border",
+    "C  circle border",
+    "G  toggle grid snapping",
+    "T  test drive (Left/Right)",
+    "N  rename",
+    "S  save",
+    "Tab  load next map",
+    "Esc  back",
+];
+
+/// What clicking in the arena places
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorTool {
+    /// Vertices of a polygonal arena border
+    Border,
+    Wall,
+    Pillar,
+    Block,
+    Spawn,
+    Zone,
+
+    /// Both ends of a portal, each placed like a spawn point
+    Portal,
+}
+
+impl EditorTool {
+    fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Border => "Border",
+            EditorTool::Wall => "Wall",
+            EditorTool::Pillar => "Pillar",
+            EditorTool::Block => "Block",
+            EditorTool::Spawn => "Spawn",
+            EditorTool::Zone => "Powermod zone",
+            EditorTool::Portal => "Portal",
+        }
+    }
+}
+
+/// Text buffer for renaming the map
+#[derive(Debug)]
+struct MapNameInput {
+    buf: String,
+}
+
+/// Mode for drawing maps with the mouse and saving them to the maps directory
+#[derive(Debug)]
+pub struct MapEditor {
+    /// The map being edited
+    map: Map,
+
+    tool: EditorTool,
+
+    /// Points placed for the shape or obstacle under construction, in arena space
+    pending: Vec<Point2<f32>>,
+
+    /// Whether placed points snap to the grid
+    snap: bool,
+
+    /// Curve driving around the map when test driving
+    test_drive: Option<Curve>,
+
+    /// How many times the test drive curve respawned, used to cycle through spawn points
+    respawns: usize,
+
+    rename: Option<MapNameInput>,
+
+    /// Result of the last save or load and whether it was an error
+    status: Option<(String, bool, Instant)>,
+
+    /// Time the hazards are shown at, running the whole time the editor is open
+    clock: Duration,
+}
+
+impl MapEditor {
+    pub fn new() -> Self {
+        Self {
+            map: Map {
+                name: "Custom".to_string(),
+                ..Map::default()
+            },
+            tool: EditorTool::Border,
+            pending: vec![],
+            snap: true,
+            test_drive: None,
+            respawns: 0,
+            rename: None,
+            status: None,
+            clock: Duration::ZERO,
+        }
+    }
+
+    /// Process the editor's input. Returns whether the editor should be closed.
+    pub fn update(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        self.clock += ctx.time.delta();
+
+        if let Some(ref mut input) = self.rename {
+            if ctx.keyboard.is_key_just_pressed(KeyCode::Return) {
+                if !input.buf.trim().is_empty() {
+                    self.map.name = input.buf.trim().to_string();
+                }
+                self.rename = None;
+            } else if ctx.keyboard.is_key_just_pressed(KeyCode::Escape) {
+                self.rename = None;
+            } else if ctx.keyboard.is_key_just_pressed(KeyCode::Back) {
+                input.buf.pop();
+            } else if input.buf.len() <= 20 {
+                key_to_str!(ctx, input);
+            }
+            return Ok(false);
+        }
+
+        let bounds = self.bounds(ctx);
+
+        if let Some(ref mut curve) = self.test_drive {
+            if ctx.keyboard.is_key_just_pressed(KeyCode::T)
+                || ctx.keyboard.is_key_just_pressed(KeyCode::Escape)
+            {
+                self.test_drive = None;
+                return Ok(false);
+            }
+
+            let delta = ctx.time.delta().as_secs_f32();
+            self.map.enter_portal(bounds, curve, delta, Instant::now());
+            let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+
+            let crashed = self.map.shape.escapes(bounds, bbox)
+                || self
+                    .map
+                    .obstacle_lines(bounds)
+                    .iter()
+                    .chain(
+                        self.map
+                            .hazard_lines(bounds, self.clock.as_secs_f32(), 0)
+                            .iter(),
+                    )
+                    .any(|line| check_bbox_colision(bbox, line));
+
+            if crashed {
+                self.respawns += 1;
+                Self::respawn(&self.map, bounds, curve, self.respawns);
+            } else {
+                curve.rotate(ctx);
+                curve.tick_trail(delta);
+                curve.mv(delta);
+            }
+
+            return Ok(false);
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::Escape) {
+            if self.pending.is_empty() {
+                return Ok(true);
+            }
+            self.pending.clear();
+        }
+
+        let tools = [
+            (KeyCode::Key1, EditorTool::Border),
+            (KeyCode::Key2, EditorTool::Wall),
+            (KeyCode::Key3, EditorTool::Pillar),
+            (KeyCode::Key4, EditorTool::Block),
+            (KeyCode::Key5, EditorTool::Spawn),
+            (KeyCode::Key6, EditorTool::Zone),
+            (KeyCode::Key7, EditorTool::Portal),
+        ];
+
+        for (key, tool) in tools {
+            if ctx.keyboard.is_key_just_pressed(key) && self.tool != tool {
+                self.tool = tool;
+                self.pending.clear();
+            }
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::G) {
+            self.snap = !self.snap;
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::R) {
+            self.map.shape = ArenaShape::Rectangle;
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::C) {
+            self.map.shape = ArenaShape::Circle;
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::Return)
+            && self.tool == EditorTool::Border
+            && self.pending.len() >= 3
+        {
+            // Only accept a border the map can be loaded with
+            match map::check_polygon(&self.pending) {
+                Ok(()) => self.map.shape = ArenaShape::Polygon(std::mem::take(&mut self.pending)),
+                Err(reason) => self.status = Some((reason, true, Instant::now())),
+            }
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::N) {
+            self.rename = Some(MapNameInput {
+                buf: self.map.name.clone(),
+            });
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::S) {
+            self.status = Some(match self.map.save(ctx) {
+                Ok(path) => (
+                    format!("Saved to {}", path.display()),
+                    false,
+                    Instant::now(),
+                ),
+                Err(e) => (e.to_string(), true, Instant::now()),
+            });
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::Tab) {
+            self.load_next(ctx);
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::T) {
+            let mut curve = Curve::new_random_pos(
+                ctx,
+                0,
+                bounds,
+                TEST_DRIVE_KEYS,
+                Color::GREEN,
+                true,
+                DEFAULT_VELOCITY,
+            )?;
+            self.respawns = 0;
+            Self::respawn(&self.map, bounds, &mut curve, 0);
+            self.pending.clear();
+            self.test_drive = Some(curve);
+            return Ok(false);
+        }
+
+        if ctx.mouse.button_just_pressed(MouseButton::Right) {
+            if self.pending.is_empty() {
+                self.undo();
+            } else {
+                self.pending.clear();
+            }
+        }
+
+        if ctx.mouse.button_just_pressed(MouseButton::Left) {
+            let point = self.cursor(ctx, bounds);
+            self.place(point);
+        }
+
+        Ok(false)
+    }
+
+    /// The bounds the map is edited in, the same as during a round
+    fn bounds(&self, ctx: &Context) -> ArenaBounds {
+        ArenaBounds::new_center(ctx.gfx.drawable_size(), self.map.size)
+    }
+
+    /// The mouse position in arena space
+    fn cursor(&self, ctx: &Context, bounds: ArenaBounds) -> Point2<f32> {
+        let (center, radius) = bounds.inscribed();
+        let mouse = ctx.mouse.position();
+
+        let snap = |v: f32| {
+            let v = v.clamp(-1., 1.);
+            if self.snap {
+                (v / GRID_STEP).round() * GRID_STEP
+            } else {
+                v
+            }
+        };
+
+        Point2 {
+            x: snap((mouse.x - center.x) / radius),
+            y: snap((mouse.y - center.y) / radius),
+        }
+    }
+
+    /// Add a point for the current tool, finishing the item if it has all its points
+    fn place(&mut self, point: Point2<f32>) {
+        if self.tool == EditorTool::Border {
+            // Ignore a second click on the same spot
+            if self.pending.last() != Some(&point) {
+                self.pending.push(point);
+            }
+            return;
+        }
+
+        // Spawn points and portal ends have to be somewhere the map can be loaded with
+        let what = match self.tool {
+            EditorTool::Spawn if self.pending.is_empty() => Some(("spawn point", 0.)),
+            EditorTool::Portal if self.pending.len().is_multiple_of(2) => {
+                Some(("portal end", PORTAL_RADIUS))
+            }
+            _ => None,
+        };
+
+        if let Some((what, radius)) = what {
+            let mut clearance = map::check_clearance(
+                &self.map.shape,
+                &self.map.obstacles,
+                &self.map.portals,
+                &self.map.hazards,
+                point,
+                radius,
+                what,
+            );
+
+            // The first end of the portal being placed is not part of the map yet
+            if let [first, _] = self.pending[..] {
+                if self.tool == EditorTool::Portal && distance(first, point) < radius * 2. {
+                    let Point2 { x, y } = point;
+                    clearance = Err(format!("{what} [{x}, {y}] overlaps a portal end"));
+                }
+            }
+
+            if let Err(reason) = clearance {
+                self.status = Some((reason, true, Instant::now()));
+                return;
+            }
+        }
+
+        if self.tool == EditorTool::Portal {
+            // Every second click sets where the end placed before it faces
+            if !self.pending.len().is_multiple_of(2) && self.pending.last() == Some(&point) {
+                return;
+            }
+
+            self.pending.push(point);
+
+            if let [a, a_facing, b, b_facing] = self.pending[..] {
+                let end = |center: Point2<f32>, facing: Point2<f32>| PortalEnd {
+                    center,
+                    rotation: (facing.y - center.y).atan2(facing.x - center.x),
+                };
+                self.map.portals.push(Portal {
+                    ends: [end(a, a_facing), end(b, b_facing)],
+                    radius: PORTAL_RADIUS,
+                });
+                self.pending.clear();
+            }
+
+            return;
+        }
+
+        let Some(&start) = self.pending.first() else {
+            self.pending.push(point);
+            return;
+        };
+
+        let length = distance(start, point);
+
+        // Ignore a second click on the same spot
+        if length == 0. {
+            return;
+        }
+
+        match self.tool {
+            EditorTool::Border | EditorTool::Portal => unreachable!(),
+            EditorTool::Wall => self.map.obstacles.push(Obstacle::Wall {
+                from: start,
+                to: point,
+            }),
+            EditorTool::Pillar => self.map.obstacles.push(Obstacle::Pillar {
+                center: start,
+                radius: length,
+            }),
+            EditorTool::Block => {
+                if start.x == point.x || start.y == point.y {
+                    return;
+                }
+                self.map.obstacles.push(Obstacle::Block {
+                    min: Point2 {
+                        x: start.x.min(point.x),
+                        y: start.y.min(point.y),
+                    },
+                    max: Point2 {
+                        x: start.x.max(point.x),
+                        y: start.y.max(point.y),
+                    },
+                })
+            }
+            EditorTool::Spawn => self.map.spawns.push(Spawn {
+                point: start,
+                rotation: (point.y - start.y).atan2(point.x - start.x),
+            }),
+            EditorTool::Zone => self.map.powermod_zones.push(Zone {
+                center: start,
+                radius: length,
+            }),
+        }
+
+        self.pending.clear();
+    }
+
+    /// Remove the last item placed with the current tool
+    fn undo(&mut self) {
+        let obstacle = |tool, obstacle: &Obstacle| {
+            matches!(
+                (tool, obstacle),
+                (EditorTool::Wall, Obstacle::Wall { .. })
+                    | (EditorTool::Pillar, Obstacle::Pillar { .. })
+                    | (EditorTool::Block, Obstacle::Block { .. })
+            )
+        };
+
+        match self.tool {
+            EditorTool::Border => {}
+            EditorTool::Wall | EditorTool::Pillar | EditorTool::Block => {
+                if let Some(i) = self
+                    .map
+                    .obstacles
+                    .iter()
+                    .rposition(|o| obstacle(self.tool, o))
+                {
+                    self.map.obstacles.remove(i);
+                }
+            }
+            EditorTool::Spawn => {
+                self.map.spawns.pop();
+            }
+            EditorTool::Zone => {
+                self.map.powermod_zones.pop();
+            }
+            EditorTool::Portal => {
+                self.map.portals.pop();
+            }
+        }
+    }
+
+    /// Replace the edited map with the map after it in the maps directory
+    fn load_next(&mut self, ctx: &mut Context) {
+        let (maps, errors) = Map::load_all(ctx);
+
+        let current = maps
+            .iter()
+            .position(|map| map.name == self.map.name)
+            .map_or(0, |i| i + 1);
+
+        if let Some(map) = maps.get(current % maps.len()) {
+            self.map = map.clone();
+            self.pending.clear();
+            self.status = Some(match errors.first() {
+                Some(e) => (e.to_string(), true, Instant::now()),
+                None => (format!("Loaded {}", map.name), false, Instant::now()),
+            });
+        }
+    }
+
+    /// Put the test drive curve back at a spawn point with a fresh trail
+    fn respawn(map: &Map, bounds: ArenaBounds, curve: &mut Curve, respawns: usize) {
+        match map.spawns.get(respawns % map.spawns.len().max(1)) {
+            Some(spawn) => {
+                curve.position = bounds.place(spawn.point);
+                curve.rotation = spawn.rotation;
+            }
+            None => curve.position = map.random_pos(bounds, SPAWN_OBSTACLE_DISTANCE),
+        }
+
+        curve.trail.clear();
+        curve.trail_active = true;
+        curve.trail_fuse = Curve::new_trail_fuse();
+        curve.trail_ts = Instant::now();
+        curve.portal_exit = None;
+        curve.shield_break = None;
+    }
+}
+
+impl Default for MapEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drawing logic impls
+impl MapEditor {
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let bounds = self.bounds(ctx);
+        let (center, radius) = bounds.inscribed();
+
+        let arena = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &self.map.shape.outline(bounds),
+            Color::from_rgb(30, 30, 30),
+        )?;
+        canvas.draw(&arena, DrawParam::default());
+
+        // Grid covering arena space
+
+        if self.snap {
+            let mut builder = graphics::MeshBuilder::new();
+            let color = Color::new(1., 1., 1., 0.06);
+            let lines = (2. / GRID_STEP).round() as usize;
+
+            for i in 0..=lines {
+                let offset = -radius + radius * 2. * i as f32 / lines as f32;
+                builder.line(
+                    &[
+                        Point2 {
+                            x: center.x + offset,
+                            y: center.y - radius,
+                        },
+                        Point2 {
+                            x: center.x + offset,
+                            y: center.y + radius,
+                        },
+                    ],
+                    1.,
+                    color,
+                )?;
+                builder.line(
+                    &[
+                        Point2 {
+                            x: center.x - radius,
+                            y: center.y + offset,
+                        },
+                        Point2 {
+                            x: center.x + radius,
+                            y: center.y + offset,
+                        },
+                    ],
+                    1.,
+                    color,
+                )?;
+            }
+
+            canvas.draw(
+                &graphics::Mesh::from_data(ctx, builder.build()),
+                DrawParam::default(),
+            );
+        }
+
+        draw_obstacles(ctx, canvas, &self.map.obstacles, bounds, OBSTACLE_COLOR)?;
+        draw_obstacles(
+            ctx,
+            canvas,
+            &self.map.hazards_at(self.clock.as_secs_f32(), 0),
+            bounds,
+            HAZARD_COLOR,
+        )?;
+        draw_portals(ctx, canvas, &self.map, bounds)?;
+
+        // Powermod zones and spawn points
+
+        for zone in self.map.powermod_zones.iter() {
+            let ring = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::stroke(2.),
+                bounds.place(zone.center),
+                zone.radius * radius,
+                0.5,
+                PowerTarget::Collector.color(),
+            )?;
+            canvas.draw(&ring, DrawParam::default());
+        }
+
+        for spawn in self.map.spawns.iter() {
+            self.draw_spawn(ctx, canvas, bounds.place(spawn.point), spawn.rotation)?;
+        }
+
+        if let Some(ref curve) = self.test_drive {
+            draw_curve(ctx, canvas, curve)?;
+        } else {
+            self.draw_pending(ctx, canvas, bounds)?;
+        }
+
+        // Help and status
+
+        let lines = [
+            format!("Map: {}", self.map.name),
+            format!("Tool: {}", self.tool.name()),
+            format!("Grid snapping: {}", if self.snap { "on" } else { "off" }),
+            String::new(),
+        ]
+        .into_iter()
+        .chain(HELP.iter().map(|line| line.to_string()));
+
+        for (i, line) in lines.enumerate() {
+            let mut text = graphics::Text::new(line);
+            text.set_scale(PxScale::from(16.));
+            canvas.draw(
+                &text,
+                DrawParam::default().dest(Point2 {
+                    x: 20.,
+                    y: 20. + i as f32 * 20.,
+                }),
+            );
+        }
+
+        if let Some((ref message, error, at)) = self.status {
+            if Instant::now().duration_since(at) < STATUS_DURATION {
+                let (_, y) = ctx.gfx.drawable_size();
+                let color = if error {
+                    Color::from_rgb(220, 80, 80)
+                } else {
+                    Color::from_rgb(120, 220, 120)
+                };
+
+                let mut text = graphics::Text::new(message.as_str());
+                text.set_scale(PxScale::from(16.));
+                text.fragments_mut()
+                    .iter_mut()
+                    .for_each(|frag| frag.color = Some(color));
+                canvas.draw(
+                    &text,
+                    DrawParam::default().dest(Point2 { x: 20., y: y - 40. }),
+                );
+            }
+        }
+
+        if let Some(ref input) = self.rename {
+            let (x, y) = ctx.gfx.drawable_size();
+
+            let mut text = graphics::Text::new(format!("Map name: {}_", input.buf));
+            text.set_scale(PxScale::from(24.));
+            let dims = text.dimensions(ctx).unwrap();
+
+            let background = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(
+                    x * 0.5 - dims.w * 0.5 - 20.,
+                    y * 0.5 - dims.h * 0.5 - 20.,
+                    dims.w + 40.,
+                    dims.h + 40.,
+                ),
+                Color::from_rgb(50, 50, 50),
+            )?;
+            canvas.draw(&background, DrawParam::default());
+            canvas.draw(
+                &text,
+                DrawParam::default().dest(Point2 {
+                    x: x * 0.5 - dims.w * 0.5,
+                    y: y * 0.5 - dims.h * 0.5,
+                }),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// A spawn point with an arrow showing where the curve heads
+    fn draw_spawn(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        point: Point2<f32>,
+        rotation: f32,
+    ) -> GameResult {
+        let dot = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            point,
+            4.,
+            0.5,
+            Color::WHITE,
+        )?;
+        let heading = graphics::Mesh::new_line(
+            ctx,
+            &[
+                point,
+                Point2 {
+                    x: point.x + 20. * rotation.cos(),
+                    y: point.y + 20. * rotation.sin(),
+                },
+            ],
+            2.,
+            Color::WHITE,
+        )?;
+        canvas.draw(&dot, DrawParam::default());
+        canvas.draw(&heading, DrawParam::default());
+        Ok(())
+    }
+
+    /// Preview of the item under construction following the mouse
+    fn draw_pending(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        bounds: ArenaBounds,
+    ) -> GameResult {
+        let cursor = self.cursor(ctx, bounds);
+        let mouse = bounds.place(cursor);
+        let color = Color::new(1., 1., 1., 0.6);
+
+        let crosshair =
+            graphics::Mesh::new_circle(ctx, graphics::DrawMode::stroke(1.), mouse, 3., 0.5, color)?;
+        canvas.draw(&crosshair, DrawParam::default());
+
+        let Some(&start) = self.pending.first() else {
+            return Ok(());
+        };
+
+        let (_, radius) = bounds.inscribed();
+        let start_screen = bounds.place(start);
+
+        let mesh = match self.tool {
+            EditorTool::Portal => {
+                let mut builder = graphics::MeshBuilder::new();
+
+                // Placed ends with the way they face, the last one following the mouse
+                for (i, center) in self.pending.iter().enumerate().step_by(2) {
+                    let center = bounds.place(*center);
+                    let facing = self
+                        .pending
+                        .get(i + 1)
+                        .map_or(mouse, |point| bounds.place(*point));
+
+                    builder.circle(
+                        graphics::DrawMode::stroke(2.),
+                        center,
+                        PORTAL_RADIUS * radius,
+                        0.5,
+                        color,
+                    )?;
+                    if facing != center {
+                        builder.line(&[center, facing], 2., color)?;
+                    }
+                }
+
+                graphics::Mesh::from_data(ctx, builder.build())
+            }
+            EditorTool::Border => {
+                let mut points = self
+                    .pending
+                    .iter()
+                    .map(|point| bounds.place(*point))
+                    .chain(std::iter::once(mouse))
+                    .collect::<Vec<_>>();
+                points.dedup();
+                if points.len() < 2 {
+                    return Ok(());
+                }
+                graphics::Mesh::new_line(ctx, &points, 2., color)?
+            }
+            EditorTool::Wall | EditorTool::Spawn => {
+                if start_screen == mouse {
+                    return Ok(());
+                }
+                graphics::Mesh::new_line(ctx, &[start_screen, mouse], 2., color)?
+            }
+            EditorTool::Pillar | EditorTool::Zone => {
+                let length = distance(start, cursor) * radius;
+                if length == 0. {
+                    return Ok(());
+                }
+                graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.),
+                    start_screen,
+                    length,
+                    0.5,
+                    color,
+                )?
+            }
+            EditorTool::Block => graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(2.),
+                graphics::Rect::new(
+                    start_screen.x.min(mouse.x),
+                    start_screen.y.min(mouse.y),
+                    (start_screen.x - mouse.x).abs(),
+                    (start_screen.y - mouse.y).abs(),
+                ),
+                color,
+            )?,
+        };
+
+        canvas.draw(&mesh, DrawParam::default());
+
+        Ok(())
+    }
+}
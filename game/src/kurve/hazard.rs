@@ -0,0 +1,303 @@
+use super::{
+    obstacle::{Obstacle, OBSTACLE_GIRTH},
+    shape::segment_distance,
+    ArenaBounds,
+};
+use ggez::mint::Point2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f32::consts::{PI, SQRT_2};
+
+/// An obstacle moving on its own during a round, killing curves on contact.
+///
+/// Where a hazard is only depends on the round time and the phase it starts at, so the same
+/// seed always plays out the same way. Coordinates, lengths and linear speeds are in arena
+/// space, see [ArenaBounds::place].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hazard {
+    /// A bar spinning around its middle
+    Sweeper {
+        pivot: Point2<f32>,
+
+        /// How far the bar reaches out from the pivot on either side
+        length: f32,
+
+        /// Radians per second, clockwise when positive
+        speed: f32,
+    },
+
+    /// A square block circling around a point
+    Orbiter {
+        center: Point2<f32>,
+
+        /// Distance between the center and the middle of the block
+        orbit: f32,
+
+        /// Half of the block's side
+        size: f32,
+
+        /// Radians per second, clockwise when positive
+        speed: f32,
+    },
+
+    /// A square block going back and forth along a path
+    Patrol {
+        /// At least 2 points
+        path: Vec<Point2<f32>>,
+
+        /// Half of the block's side
+        size: f32,
+
+        /// Distance covered per second
+        speed: f32,
+    },
+}
+
+impl Hazard {
+    /// The hazard as it is `time` seconds into the round. `phase` is the fraction of its
+    /// cycle it starts the round at.
+    pub fn at(&self, time: f32, phase: f32) -> Obstacle {
+        match self {
+            Hazard::Sweeper {
+                pivot,
+                length,
+                speed,
+            } => {
+                let angle = phase * 2. * PI + speed * time;
+                let (d_x, d_y) = (length * angle.cos(), length * angle.sin());
+                Obstacle::Wall {
+                    from: Point2 {
+                        x: pivot.x - d_x,
+                        y: pivot.y - d_y,
+                    },
+                    to: Point2 {
+                        x: pivot.x + d_x,
+                        y: pivot.y + d_y,
+                    },
+                }
+            }
+            Hazard::Orbiter {
+                center,
+                orbit,
+                size,
+                speed,
+            } => {
+                let angle = phase * 2. * PI + speed * time;
+                block(
+                    Point2 {
+                        x: center.x + orbit * angle.cos(),
+                        y: center.y + orbit * angle.sin(),
+                    },
+                    *size,
+                )
+            }
+            Hazard::Patrol { path, size, speed } => {
+                let total = path_length(path);
+
+                // Going there and back again is one cycle
+                let mut travelled = (phase * 2. * total + speed * time).rem_euclid(2. * total);
+                if travelled > total {
+                    travelled = 2. * total - travelled;
+                }
+
+                block(point_along(path, travelled), *size)
+            }
+        }
+    }
+
+    /// Distance from the point to anywhere the hazard can ever be
+    #[inline]
+    pub fn reach_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        (self.reach_outline_distance(bounds, point) - OBSTACLE_GIRTH.as_f32()).max(0.)
+    }
+
+    /// Distance from the point to anywhere the outline of the hazard can ever be. Unlike
+    /// [reach_distance](Self::reach_distance) it leaves out the thickness of the border, which
+    /// is only known in screen coordinates.
+    pub fn reach_outline_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        let (_, radius) = bounds.inscribed();
+
+        let distance = match self {
+            Hazard::Sweeper { pivot, length, .. } => {
+                super::distance(bounds.place(*pivot), point) - length * radius
+            }
+            Hazard::Orbiter {
+                center,
+                orbit,
+                size,
+                ..
+            } => {
+                (super::distance(bounds.place(*center), point) - orbit * radius).abs()
+                    - size * SQRT_2 * radius
+            }
+            Hazard::Patrol { path, size, .. } => {
+                let path = path.iter().map(|p| bounds.place(*p)).collect::<Vec<_>>();
+                path.windows(2)
+                    .map(|segment| segment_distance(point, segment[0], segment[1]))
+                    .fold(f32::INFINITY, f32::min)
+                    - size * SQRT_2 * radius
+            }
+        };
+
+        distance.max(0.)
+    }
+}
+
+/// The fraction of its cycle the `index`th hazard of a map starts a round at
+pub fn phase(seed: u64, index: usize) -> f32 {
+    StdRng::seed_from_u64(seed.wrapping_add(index as u64)).gen_range(0f32..1.)
+}
+
+/// An axis aligned square block around the center
+#[inline]
+fn block(center: Point2<f32>, size: f32) -> Obstacle {
+    Obstacle::Block {
+        min: Point2 {
+            x: center.x - size,
+            y: center.y - size,
+        },
+        max: Point2 {
+            x: center.x + size,
+            y: center.y + size,
+        },
+    }
+}
+
+#[inline]
+fn path_length(path: &[Point2<f32>]) -> f32 {
+    path.windows(2)
+        .map(|segment| super::distance(segment[0], segment[1]))
+        .sum()
+}
+
+/// The point `travelled` away from the start of the path, measured along the path
+fn point_along(path: &[Point2<f32>], mut travelled: f32) -> Point2<f32> {
+    for segment in path.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let length = super::distance(a, b);
+
+        if travelled <= length && length > 0. {
+            let t = travelled / length;
+            return Point2 {
+                x: a.x + (b.x - a.x) * t,
+                y: a.y + (b.y - a.y) * t,
+            };
+        }
+
+        travelled -= length;
+    }
+
+    path[path.len() - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Point2<f32>, expected: (f32, f32)) {
+        assert!(
+            (actual.x - expected.0).abs() < 1e-4 && (actual.y - expected.1).abs() < 1e-4,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    /// The middle of a block
+    fn block_center(obstacle: Obstacle) -> Point2<f32> {
+        match obstacle {
+            Obstacle::Block { min, max } => Point2 {
+                x: (min.x + max.x) * 0.5,
+                y: (min.y + max.y) * 0.5,
+            },
+            other => panic!("expected a block, got {other:?}"),
+        }
+    }
+
+    fn hazards() -> Vec<Hazard> {
+        vec![
+            Hazard::Sweeper {
+                pivot: Point2 { x: 0., y: 0. },
+                length: 0.4,
+                speed: 1.,
+            },
+            Hazard::Orbiter {
+                center: Point2 { x: 0.2, y: -0.1 },
+                orbit: 0.5,
+                size: 0.05,
+                speed: -0.7,
+            },
+            Hazard::Patrol {
+                path: vec![Point2 { x: -0.5, y: 0.5 }, Point2 { x: 0.5, y: 0.5 }],
+                size: 0.05,
+                speed: 0.3,
+            },
+        ]
+    }
+
+    #[test]
+    fn same_seed_plays_out_the_same() {
+        for time in [0., 1.5, 12.25] {
+            for (i, hazard) in hazards().iter().enumerate() {
+                assert_eq!(
+                    hazard.at(time, phase(42, i)),
+                    hazard.at(time, phase(42, i)),
+                    "hazard {i} at {time}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seed_changes_the_phase() {
+        for i in 0..3 {
+            assert_ne!(phase(1, i), phase(2, i), "hazard {i}");
+            assert!((0. ..1.).contains(&phase(1, i)));
+        }
+
+        let sweeper = &hazards()[0];
+        assert_ne!(sweeper.at(0., phase(1, 0)), sweeper.at(0., phase(2, 0)));
+    }
+
+    #[test]
+    fn sweeper_spins_around_its_pivot() {
+        let Obstacle::Wall { from, to } = hazards()[0].at(PI * 0.5, 0.) else {
+            panic!("a sweeper should be a wall");
+        };
+
+        assert_near(from, (0., -0.4));
+        assert_near(to, (0., 0.4));
+    }
+
+    #[test]
+    fn patrol_reverses_at_the_end_of_its_path() {
+        let patrol = Hazard::Patrol {
+            path: vec![Point2 { x: 0., y: 0. }, Point2 { x: 1., y: 0. }],
+            size: 0.05,
+            speed: 0.5,
+        };
+
+        assert_near(block_center(patrol.at(0., 0.)), (0., 0.));
+        assert_near(block_center(patrol.at(1., 0.)), (0.5, 0.));
+        assert_near(block_center(patrol.at(2., 0.)), (1., 0.));
+        // On its way back
+        assert_near(block_center(patrol.at(3., 0.)), (0.5, 0.));
+        assert_near(block_center(patrol.at(4., 0.)), (0., 0.));
+        assert_near(block_center(patrol.at(5., 0.)), (0.5, 0.));
+
+        // Half a cycle in, it starts at the far end
+        assert_near(block_center(patrol.at(0., 0.5)), (1., 0.));
+    }
+
+    #[test]
+    fn measures_along_every_segment() {
+        let path = [
+            Point2 { x: 0., y: 0. },
+            Point2 { x: 1., y: 0. },
+            Point2 { x: 1., y: 1. },
+        ];
+
+        assert!((path_length(&path) - 2.).abs() < 1e-6);
+        assert_near(point_along(&path, 0.25), (0.25, 0.));
+        assert_near(point_along(&path, 1.), (1., 0.));
+        assert_near(point_along(&path, 1.5), (1., 0.5));
+        assert_near(point_along(&path, 3.), (1., 1.));
+    }
+}
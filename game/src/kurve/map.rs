@@ -0,0 +1,1354 @@
+use super::{
+    curve::Curve,
+    hazard::{self, Hazard},
+    obstacle::Obstacle,
+    point::Line,
+    portal::{Portal, PortalEnd},
+    shape::{self, edges, ArenaShape},
+    ArenaBounds, PLACEMENT_ATTEMPTS, SIZE_SMALL,
+};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameError};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+/// The directory map files are loaded from
+pub const MAPS_DIR: &str = "/maps";
+
+/// Maps shipped with the game. With the `embed` feature they are compiled into the binary.
+const BUNDLED_MAPS: [Resource; 7] = [
+    resource!("maps/circle.toml"),
+    resource!("maps/cross.toml"),
+    resource!("maps/hexagon.toml"),
+    resource!("maps/pillars.toml"),
+    resource!("maps/portals.toml"),
+    resource!("maps/walls.toml"),
+    resource!("maps/windmill.toml"),
+];
+
+/// Extension of map files
+const MAP_EXTENSION: &str = "toml";
+
+/// How close polygon points can be to lining up, in arena space
+const POLYGON_EPSILON: f32 = 1e-5;
+
+/// How far spawn points and the edges of portal ends have to be from obstacles, in arena space
+const OBSTACLE_CLEARANCE: f32 = 0.05;
+
+/// An arena layout. Every point is in arena space, see [ArenaBounds::place].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub name: String,
+
+    /// Fraction of the screen width and height the arena takes up during a round
+    pub size: (f32, f32),
+
+    pub shape: ArenaShape,
+
+    pub obstacles: Vec<Obstacle>,
+
+    /// Where curves start their rounds. Curves without a spawn point start anywhere.
+    pub spawns: Vec<Spawn>,
+
+    /// Areas powermods appear in. Powermods appear anywhere if there are none.
+    pub powermod_zones: Vec<Zone>,
+
+    pub portals: Vec<Portal>,
+
+    pub hazards: Vec<Hazard>,
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self {
+            name: "Classic".to_string(),
+            size: SIZE_SMALL,
+            shape: ArenaShape::Rectangle,
+            obstacles: vec![],
+            spawns: vec![],
+            powermod_zones: vec![],
+            portals: vec![],
+            hazards: vec![],
+        }
+    }
+}
+
+/// A fixed starting point of a curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spawn {
+    pub point: Point2<f32>,
+
+    /// Initial rotation in radians
+    pub rotation: f32,
+}
+
+/// A circular area
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zone {
+    pub center: Point2<f32>,
+    pub radius: f32,
+}
+
+impl Map {
+    /// Load the built-in map, the bundled maps and then every other map in the maps directory,
+    /// sorted by file name. Files that fail to load are returned separately so they can be
+    /// reported.
+    pub fn load_all(ctx: &mut Context) -> (Vec<Map>, Vec<MapError>) {
+        let mut files = ctx
+            .fs
+            .read_dir(MAPS_DIR)
+            .map(|files| {
+                files
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|ext| ext.to_str() == Some(MAP_EXTENSION))
+                    })
+                    .filter(|path| {
+                        !BUNDLED_MAPS
+                            .iter()
+                            .any(|bundled| path == Path::new(bundled.path))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        files.sort();
+
+        let mut maps = vec![Map::default()];
+        let mut errors = vec![];
+
+        for resource in &BUNDLED_MAPS {
+            match Self::load_resource(ctx, resource) {
+                Ok(map) => maps.push(map),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        for path in files {
+            match Map::load(ctx, &path) {
+                Ok(map) => maps.push(map),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (maps, errors)
+    }
+
+    /// Load a single map from the game's filesystem
+    pub fn load(ctx: &mut Context, path: &Path) -> Result<Self, MapError> {
+        let mut contents = String::new();
+
+        ctx.fs
+            .open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents).map_err(GameError::from))
+            .map_err(|e| MapError::Io(path.to_path_buf(), e))?;
+
+        Self::parse_file(path, &contents)
+    }
+
+    /// Load one of the maps shipped with the game
+    fn load_resource(ctx: &mut Context, resource: &Resource) -> Result<Self, MapError> {
+        let path = Path::new(resource.path);
+
+        let bytes = resource
+            .bytes(ctx)
+            .map_err(|e| MapError::Io(path.to_path_buf(), e))?;
+
+        let contents = std::str::from_utf8(&bytes).map_err(|e| {
+            MapError::Io(
+                path.to_path_buf(),
+                GameError::ResourceLoadError(e.to_string()),
+            )
+        })?;
+
+        Self::parse_file(path, contents)
+    }
+
+    /// Parse the contents of the map file at `path`
+    fn parse_file(path: &Path, contents: &str) -> Result<Self, MapError> {
+        Self::parse(contents).map_err(|kind| match kind {
+            ParseError::Syntax(e) => MapError::Syntax(path.to_path_buf(), e),
+            ParseError::Invalid(reason) => MapError::Invalid(path.to_path_buf(), reason),
+        })
+    }
+
+    /// Write the map to the maps directory in the user's data directory, named after the map.
+    /// Returns the path of the written file.
+    pub fn save(&self, ctx: &mut Context) -> Result<PathBuf, MapError> {
+        let file_name = self
+            .name
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect::<String>();
+
+        let path = Path::new(MAPS_DIR).join(format!("{file_name}.{MAP_EXTENSION}"));
+
+        let contents = toml::to_string(&MapFile::from(self))
+            .map_err(|e| MapError::Invalid(path.clone(), e.to_string()))?;
+
+        // Never write a file that would fail to load
+        if let Err(ParseError::Invalid(reason)) = Self::parse(&contents) {
+            return Err(MapError::Invalid(path, reason));
+        }
+
+        ctx.fs
+            .create_dir(MAPS_DIR)
+            .and_then(|_| ctx.fs.create(&path))
+            .and_then(|mut file| file.write_all(contents.as_bytes()).map_err(GameError::from))
+            .map_err(|e| MapError::Io(path.clone(), e))?;
+
+        Ok(path)
+    }
+
+    /// Parse and validate the contents of a map file
+    fn parse(contents: &str) -> Result<Self, ParseError> {
+        let file: MapFile = toml::from_str(contents).map_err(ParseError::Syntax)?;
+        file.validate().map_err(ParseError::Invalid)
+    }
+
+    /// Whether the point is inside the arena and at least `margin` away from its border, any
+    /// obstacle, any portal and anywhere a hazard can get to. Obstacles, portals and hazards are
+    /// placed in `arena` while the shape is fit into `border`, which is smaller than `arena` once
+    /// the arena shrinks in sudden death.
+    pub fn fits(
+        &self,
+        arena: ArenaBounds,
+        border: ArenaBounds,
+        point: Point2<f32>,
+        margin: f32,
+    ) -> bool {
+        self.shape.contains(border, point)
+            && self.shape.border_distance(border, point) >= margin
+            && self.obstacle_distance(arena, point) >= margin
+            && self.portal_distance(arena, point) >= margin
+            && self.hazard_distance(arena, point) >= margin
+    }
+
+    /// Return a random point that [fits](Self::fits). Falls back to the center if no
+    /// such point is found.
+    pub fn random_pos(&self, bounds: ArenaBounds, margin: f32) -> Point2<f32> {
+        (0..PLACEMENT_ATTEMPTS)
+            .map(|_| bounds.random_pos())
+            .find(|point| self.fits(bounds, bounds, *point, margin))
+            .unwrap_or_else(|| bounds.inscribed().0)
+    }
+
+    /// The borders of every obstacle as lines curves collide with
+    pub fn obstacle_lines(&self, bounds: ArenaBounds) -> Vec<Line> {
+        self.obstacles
+            .iter()
+            .flat_map(|obstacle| obstacle.lines(bounds))
+            .collect()
+    }
+
+    /// Distance from the point to the closest obstacle
+    pub fn obstacle_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        self.obstacles
+            .iter()
+            .map(|obstacle| obstacle.distance(bounds, point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Every hazard as it is `time` seconds into a round played with the seed
+    pub fn hazards_at(&self, time: f32, seed: u64) -> Vec<Obstacle> {
+        self.hazards
+            .iter()
+            .enumerate()
+            .map(|(i, hazard)| hazard.at(time, hazard::phase(seed, i)))
+            .collect()
+    }
+
+    /// The borders of every hazard as lines curves collide with, see [hazards_at](Self::hazards_at)
+    pub fn hazard_lines(&self, bounds: ArenaBounds, time: f32, seed: u64) -> Vec<Line> {
+        self.hazards_at(time, seed)
+            .iter()
+            .flat_map(|hazard| hazard.lines(bounds))
+            .collect()
+    }
+
+    /// Distance from the point to the closest place a hazard can get to
+    pub fn hazard_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        self.hazards
+            .iter()
+            .map(|hazard| hazard.reach_distance(bounds, point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Distance from the point to the edge of the closest portal end
+    pub fn portal_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        self.portals
+            .iter()
+            .flat_map(|portal| {
+                let radius = portal.screen_radius(bounds);
+                portal.ends.iter().map(move |end| {
+                    (super::distance(bounds.place(end.center), point) - radius).max(0.)
+                })
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// If the curve's next position is in a portal, move it out of the other end. Curves still
+    /// in their [grace](Curve::in_portal_grace) from the last jump are left alone. Returns
+    /// whether the curve was moved.
+    pub fn enter_portal(
+        &self,
+        bounds: ArenaBounds,
+        curve: &mut Curve,
+        delta: f32,
+        now: Instant,
+    ) -> bool {
+        if curve.in_portal_grace(now) {
+            return false;
+        }
+
+        let next_pos = curve.next_pos(delta);
+
+        for portal in self.portals.iter() {
+            if let Some(end) = portal.entered(bounds, next_pos) {
+                portal.teleport(bounds, curve, end);
+                curve.portal_exit = Some(now);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// A random point powermods could appear at. Stays within a powermod zone if the map has
+    /// any, otherwise it can be anywhere within the bounds.
+    pub fn powermod_pos(&self, bounds: ArenaBounds) -> Point2<f32> {
+        let mut rng = rand::thread_rng();
+
+        if self.powermod_zones.is_empty() {
+            return bounds.random_pos();
+        }
+
+        let zone = self.powermod_zones[rng.gen_range(0..self.powermod_zones.len())];
+
+        // Square root keeps the points evenly spread over the area
+        let distance = zone.radius * rng.gen_range(0f32..1.).sqrt();
+        let angle = rng.gen_range(0f32..2. * std::f32::consts::PI);
+
+        bounds.place(Point2 {
+            x: zone.center.x + distance * angle.cos(),
+            y: zone.center.y + distance * angle.sin(),
+        })
+    }
+}
+
+/// Why a map file could not be loaded or saved
+#[derive(Debug)]
+pub enum MapError {
+    /// The file could not be read or written
+    Io(PathBuf, GameError),
+
+    /// The file is not valid TOML or does not match the map format
+    Syntax(PathBuf, toml::de::Error),
+
+    /// The file is well formed but describes an unusable map
+    Invalid(PathBuf, String),
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::Io(path, e) => write!(f, "{}: cannot access file: {e}", path.display()),
+            MapError::Syntax(path, e) => write!(f, "{}: {e}", path.display()),
+            MapError::Invalid(path, reason) => write!(f, "{}: {reason}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+enum ParseError {
+    Syntax(toml::de::Error),
+    Invalid(String),
+}
+
+/// A map as written in a map file.
+///
+/// ```toml
+/// name = "Pillars"
+/// size = [0.35, 0.55]         # optional, fraction of the screen
+/// shape = { type = "circle" }  # "rectangle", "circle", "regular" with `sides`
+///                               # or "polygon" with `points = [[x, y], ..]`
+///
+/// [[obstacles]]
+/// type = "pillar"
+/// center = [0.5, 0.0]
+/// radius = 0.08
+///
+/// [[obstacles]]
+/// type = "wall"
+/// from = [-0.5, -0.2]
+/// to = [-0.5, 0.2]
+///
+/// [[obstacles]]
+/// type = "block"
+/// min = [-0.1, -0.1]
+/// max = [0.1, 0.1]
+///
+/// [[spawns]]
+/// point = [-0.6, 0.0]
+/// heading = 0.0               # degrees, clockwise from pointing right
+///
+/// [[powermod_zones]]
+/// center = [0.0, 0.0]
+/// radius = 0.4
+///
+/// [[portals]]
+/// radius = 0.06
+/// ends = [
+///     { point = [-0.7, 0.0], heading = 0.0 },   # a curve entering heading this way
+///     { point = [0.7, 0.0], heading = 180.0 },  # comes out of the other end heading this way
+/// ]
+///
+/// [[hazards]]
+/// type = "sweeper"            # a bar spinning around its middle
+/// pivot = [0.0, 0.0]
+/// length = 0.4                # reach on either side of the pivot
+/// speed = 45.0                # degrees per second, negative spins counterclockwise
+///
+/// [[hazards]]
+/// type = "orbiter"            # a block circling around a point
+/// center = [0.0, 0.0]
+/// orbit = 0.6
+/// size = 0.05                 # half of the block's side
+/// speed = -30.0
+///
+/// [[hazards]]
+/// type = "patrol"             # a block going back and forth along a path
+/// path = [[-0.6, -0.6], [0.6, -0.6]]
+/// size = 0.05
+/// speed = 0.2                 # arena units per second
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MapFile {
+    name: String,
+    #[serde(default = "default_size")]
+    size: [f32; 2],
+    #[serde(default)]
+    shape: ShapeDef,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    obstacles: Vec<ObstacleDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spawns: Vec<SpawnDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    powermod_zones: Vec<ZoneDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    portals: Vec<PortalDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hazards: Vec<HazardDef>,
+}
+
+fn default_size() -> [f32; 2] {
+    [SIZE_SMALL.0, SIZE_SMALL.1]
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ShapeDef {
+    #[default]
+    Rectangle,
+    Circle,
+    Regular {
+        sides: usize,
+    },
+    Polygon {
+        points: Vec<[f32; 2]>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObstacleDef {
+    Wall { from: [f32; 2], to: [f32; 2] },
+    Pillar { center: [f32; 2], radius: f32 },
+    Block { min: [f32; 2], max: [f32; 2] },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpawnDef {
+    point: [f32; 2],
+    #[serde(default)]
+    heading: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZoneDef {
+    center: [f32; 2],
+    radius: f32,
+}
+
+/// Portal ends are written like spawn points
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortalDef {
+    radius: f32,
+    ends: [SpawnDef; 2],
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum HazardDef {
+    Sweeper {
+        pivot: [f32; 2],
+        length: f32,
+        speed: f32,
+    },
+    Orbiter {
+        center: [f32; 2],
+        orbit: f32,
+        size: f32,
+        speed: f32,
+    },
+    Patrol {
+        path: Vec<[f32; 2]>,
+        size: f32,
+        speed: f32,
+    },
+}
+
+impl MapFile {
+    /// Convert to a map, making sure everything fits in the arena
+    fn validate(self) -> Result<Map, String> {
+        if self.name.trim().is_empty() {
+            return Err("`name` must not be empty".to_string());
+        }
+
+        let [width, height] = self.size;
+        if !(width > 0. && width <= 1. && height > 0. && height <= 1.) {
+            return Err(format!(
+                "`size` must be between 0 and 1 on both axes, got [{width}, {height}]"
+            ));
+        }
+
+        let shape = match self.shape {
+            ShapeDef::Rectangle => ArenaShape::Rectangle,
+            ShapeDef::Circle => ArenaShape::Circle,
+            ShapeDef::Regular { sides } if sides < 3 => {
+                return Err(format!(
+                    "a regular shape needs at least 3 sides, got {sides}"
+                ));
+            }
+            ShapeDef::Regular { sides } => ArenaShape::regular(sides),
+            ShapeDef::Polygon { points } => {
+                let points = points
+                    .into_iter()
+                    .map(|p| arena_point(p, "polygon point"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                check_polygon(&points)?;
+                ArenaShape::Polygon(points)
+            }
+        };
+
+        let obstacles: Vec<Obstacle> = self
+            .obstacles
+            .into_iter()
+            .map(|obstacle| {
+                Ok(match obstacle {
+                    ObstacleDef::Wall { from, to } => Obstacle::Wall {
+                        from: arena_point(from, "wall start")?,
+                        to: arena_point(to, "wall end")?,
+                    },
+                    ObstacleDef::Pillar { center, radius } => {
+                        if !positive(radius) {
+                            return Err(format!("pillar radius must be positive, got {radius}"));
+                        }
+                        Obstacle::Pillar {
+                            center: arena_point(center, "pillar center")?,
+                            radius,
+                        }
+                    }
+                    ObstacleDef::Block { min, max } => {
+                        if min[0] >= max[0] || min[1] >= max[1] {
+                            return Err(format!(
+                                "block `min` must be smaller than `max` on both axes, got {min:?} and {max:?}"
+                            ));
+                        }
+                        Obstacle::Block {
+                            min: arena_point(min, "block corner")?,
+                            max: arena_point(max, "block corner")?,
+                        }
+                    }
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let powermod_zones = self
+            .powermod_zones
+            .into_iter()
+            .map(|zone| {
+                if !positive(zone.radius) {
+                    return Err(format!(
+                        "powermod zone radius must be positive, got {}",
+                        zone.radius
+                    ));
+                }
+                Ok(Zone {
+                    center: arena_point(zone.center, "powermod zone center")?,
+                    radius: zone.radius,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let hazards: Vec<Hazard> = self
+            .hazards
+            .into_iter()
+            .map(|hazard| {
+                Ok(match hazard {
+                    HazardDef::Sweeper {
+                        pivot,
+                        length,
+                        speed,
+                    } => {
+                        if !positive(length) {
+                            return Err(format!("sweeper length must be positive, got {length}"));
+                        }
+                        Hazard::Sweeper {
+                            pivot: arena_point(pivot, "sweeper pivot")?,
+                            length,
+                            speed: finite(speed, "sweeper speed")?.to_radians(),
+                        }
+                    }
+                    HazardDef::Orbiter {
+                        center,
+                        orbit,
+                        size,
+                        speed,
+                    } => {
+                        if !(orbit.is_finite() && orbit >= 0. && positive(size)) {
+                            return Err(format!(
+                                "orbiter `size` must be positive and `orbit` a number not below zero, got {size} and {orbit}"
+                            ));
+                        }
+                        Hazard::Orbiter {
+                            center: arena_point(center, "orbiter center")?,
+                            orbit,
+                            size,
+                            speed: finite(speed, "orbiter speed")?.to_radians(),
+                        }
+                    }
+                    HazardDef::Patrol { path, size, speed } => {
+                        if !positive(size) {
+                            return Err(format!("patrol size must be positive, got {size}"));
+                        }
+                        if path.windows(2).all(|segment| segment[0] == segment[1]) {
+                            return Err(
+                                "a patrol path needs at least 2 different points".to_string()
+                            );
+                        }
+                        Hazard::Patrol {
+                            path: path
+                                .into_iter()
+                                .map(|p| arena_point(p, "patrol path point"))
+                                .collect::<Result<_, _>>()?,
+                            size,
+                            speed: finite(speed, "patrol speed")?,
+                        }
+                    }
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let mut portals: Vec<Portal> = vec![];
+
+        for portal in self.portals {
+            if !positive(portal.radius) {
+                return Err(format!(
+                    "portal radius must be positive, got {}",
+                    portal.radius
+                ));
+            }
+
+            let end = |end: &SpawnDef| -> Result<PortalEnd, String> {
+                let center = arena_point(end.point, "portal end")?;
+                check_clearance(
+                    &shape,
+                    &obstacles,
+                    &portals,
+                    &hazards,
+                    center,
+                    portal.radius,
+                    "portal end",
+                )?;
+                Ok(PortalEnd {
+                    center,
+                    rotation: heading(end.heading, "portal end")?,
+                })
+            };
+
+            let ends = [end(&portal.ends[0])?, end(&portal.ends[1])?];
+
+            if super::distance(ends[0].center, ends[1].center) < portal.radius * 2. {
+                let Point2 { x, y } = ends[1].center;
+                return Err(format!("portal end [{x}, {y}] overlaps another portal end"));
+            }
+
+            portals.push(Portal {
+                ends,
+                radius: portal.radius,
+            });
+        }
+
+        let spawns = self
+            .spawns
+            .into_iter()
+            .map(|spawn| {
+                let point = arena_point(spawn.point, "spawn point")?;
+                check_clearance(
+                    &shape,
+                    &obstacles,
+                    &portals,
+                    &hazards,
+                    point,
+                    0.,
+                    "spawn point",
+                )?;
+                Ok(Spawn {
+                    point,
+                    rotation: heading(spawn.heading, "spawn")?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Map {
+            name: self.name,
+            size: (width, height),
+            shape,
+            obstacles,
+            spawns,
+            powermod_zones,
+            portals,
+            hazards,
+        })
+    }
+}
+
+impl From<&Map> for MapFile {
+    fn from(map: &Map) -> Self {
+        // Keep the file readable, the editor snaps to a much coarser grid anyway
+        let round = |v: f32| (v * 1000.).round() / 1000.;
+        let point = |p: Point2<f32>| [round(p.x), round(p.y)];
+
+        let shape = match &map.shape {
+            ArenaShape::Rectangle => ShapeDef::Rectangle,
+            ArenaShape::Circle => ShapeDef::Circle,
+            ArenaShape::Polygon(points) => ShapeDef::Polygon {
+                points: points.iter().copied().map(point).collect(),
+            },
+        };
+
+        let obstacles = map
+            .obstacles
+            .iter()
+            .map(|obstacle| match *obstacle {
+                Obstacle::Wall { from, to } => ObstacleDef::Wall {
+                    from: point(from),
+                    to: point(to),
+                },
+                Obstacle::Pillar { center, radius } => ObstacleDef::Pillar {
+                    center: point(center),
+                    radius: round(radius),
+                },
+                Obstacle::Block { min, max } => ObstacleDef::Block {
+                    min: point(min),
+                    max: point(max),
+                },
+            })
+            .collect();
+
+        let spawns = map
+            .spawns
+            .iter()
+            .map(|spawn| SpawnDef {
+                point: point(spawn.point),
+                heading: round(spawn.rotation.to_degrees().rem_euclid(360.)),
+            })
+            .collect();
+
+        let powermod_zones = map
+            .powermod_zones
+            .iter()
+            .map(|zone| ZoneDef {
+                center: point(zone.center),
+                radius: round(zone.radius),
+            })
+            .collect();
+
+        let portals = map
+            .portals
+            .iter()
+            .map(|portal| PortalDef {
+                radius: round(portal.radius),
+                ends: portal.ends.map(|end| SpawnDef {
+                    point: point(end.center),
+                    heading: round(end.rotation.to_degrees().rem_euclid(360.)),
+                }),
+            })
+            .collect();
+
+        let hazards = map
+            .hazards
+            .iter()
+            .map(|hazard| match hazard {
+                Hazard::Sweeper {
+                    pivot,
+                    length,
+                    speed,
+                } => HazardDef::Sweeper {
+                    pivot: point(*pivot),
+                    length: round(*length),
+                    speed: round(speed.to_degrees()),
+                },
+                Hazard::Orbiter {
+                    center,
+                    orbit,
+                    size,
+                    speed,
+                } => HazardDef::Orbiter {
+                    center: point(*center),
+                    orbit: round(*orbit),
+                    size: round(*size),
+                    speed: round(speed.to_degrees()),
+                },
+                Hazard::Patrol { path, size, speed } => HazardDef::Patrol {
+                    path: path.iter().copied().map(point).collect(),
+                    size: round(*size),
+                    speed: round(*speed),
+                },
+            })
+            .collect();
+
+        Self {
+            name: map.name.clone(),
+            size: [round(map.size.0), round(map.size.1)],
+            shape,
+            obstacles,
+            spawns,
+            powermod_zones,
+            portals,
+            hazards,
+        }
+    }
+}
+
+/// Make sure a spawn point or a portal end of the given radius is inside the arena, does not
+/// overlap any portal and is far enough from every obstacle and hazard for a curve to get going
+pub fn check_clearance(
+    shape: &ArenaShape,
+    obstacles: &[Obstacle],
+    portals: &[Portal],
+    hazards: &[Hazard],
+    point: Point2<f32>,
+    radius: f32,
+    what: &str,
+) -> Result<(), String> {
+    // Bounds where arena space matches screen space
+    let unit = ArenaBounds {
+        x_min: -1.,
+        x_max: 1.,
+        y_min: -1.,
+        y_max: 1.,
+    };
+
+    let Point2 { x, y } = point;
+
+    if !shape.contains(unit, point) {
+        return Err(format!("{what} [{x}, {y}] is outside the arena"));
+    }
+
+    if shape.border_distance(unit, point) < radius {
+        return Err(format!("{what} [{x}, {y}] sticks out of the arena"));
+    }
+
+    let obstacle_distance = obstacles
+        .iter()
+        .map(|obstacle| obstacle.outline_distance(unit, point))
+        .fold(f32::INFINITY, f32::min);
+
+    if obstacle_distance < radius + OBSTACLE_CLEARANCE {
+        return Err(format!(
+            "{what} [{x}, {y}] must be at least {OBSTACLE_CLEARANCE} away from obstacles"
+        ));
+    }
+
+    let hazard_distance = hazards
+        .iter()
+        .map(|hazard| hazard.reach_outline_distance(unit, point))
+        .fold(f32::INFINITY, f32::min);
+
+    if hazard_distance < radius + OBSTACLE_CLEARANCE {
+        return Err(format!(
+            "{what} [{x}, {y}] must be at least {OBSTACLE_CLEARANCE} away from where hazards move"
+        ));
+    }
+
+    let overlaps = portals.iter().any(|portal| {
+        portal
+            .ends
+            .iter()
+            .any(|end| super::distance(end.center, point) < portal.radius + radius)
+    });
+
+    if overlaps {
+        return Err(format!("{what} [{x}, {y}] overlaps a portal end"));
+    }
+
+    Ok(())
+}
+
+/// Make sure the points outline an area without crossing over themselves
+pub fn check_polygon(points: &[Point2<f32>]) -> Result<(), String> {
+    if points.len() < 3 {
+        return Err(format!(
+            "a polygon needs at least 3 points, got {}",
+            points.len()
+        ));
+    }
+
+    for (i, a) in points.iter().enumerate() {
+        if points[i + 1..].contains(a) {
+            let Point2 { x, y } = a;
+            return Err(format!("polygon point [{x}, {y}] is used more than once"));
+        }
+    }
+
+    let count = points.len();
+
+    for i in 0..count {
+        let (prev, point, next) = (
+            points[(i + count - 1) % count],
+            points[i],
+            points[(i + 1) % count],
+        );
+
+        if cross(prev, point, next).abs() < POLYGON_EPSILON {
+            let Point2 { x, y } = point;
+            return Err(format!(
+                "polygon point [{x}, {y}] is in line with the points next to it"
+            ));
+        }
+    }
+
+    let edges = edges(points).collect::<Vec<_>>();
+
+    for (i, (a, b)) in edges.iter().enumerate() {
+        // Edges next to each other always meet at their shared point
+        for (c, d) in edges.iter().take(count - usize::from(i == 0)).skip(i + 2) {
+            if segments_cross(*a, *b, *c, *d) {
+                return Err("polygon edges must not cross each other".to_string());
+            }
+        }
+    }
+
+    // Twice the signed area
+    let area = edges
+        .iter()
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f32>();
+
+    if area.abs() < POLYGON_EPSILON {
+        return Err("a polygon must enclose an area".to_string());
+    }
+
+    Ok(())
+}
+
+/// Which side of the line through `a` and `b` the point `c` is on, zero if it is on the line
+#[inline]
+fn cross(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Whether the segments `a`-`b` and `c`-`d` touch or cross
+fn segments_cross(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>, d: Point2<f32>) -> bool {
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+
+    if d1 * d2 < 0. && d3 * d4 < 0. {
+        return true;
+    }
+
+    // Touching, one end lies on the other segment
+    let on = |p: Point2<f32>, from: Point2<f32>, to: Point2<f32>, side: f32| {
+        side.abs() < POLYGON_EPSILON && shape::segment_distance(p, from, to) < POLYGON_EPSILON
+    };
+
+    on(a, c, d, d1) || on(b, c, d, d2) || on(c, a, b, d3) || on(d, a, b, d4)
+}
+
+/// Whether the value is a finite number above zero
+#[inline]
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.
+}
+
+/// Make sure a value from the file is neither infinite nor NaN
+fn finite(value: f32, what: &str) -> Result<f32, String> {
+    if !value.is_finite() {
+        return Err(format!("{what} must be a number, got {value}"));
+    }
+    Ok(value)
+}
+
+/// Convert a heading from the file from degrees to radians
+fn heading(degrees: f32, what: &str) -> Result<f32, String> {
+    finite(degrees, &format!("{what} heading")).map(f32::to_radians)
+}
+
+/// Convert a point from the file, making sure it is within arena space
+fn arena_point([x, y]: [f32; 2], what: &str) -> Result<Point2<f32>, String> {
+    if !(-1. ..=1.).contains(&x) || !(-1. ..=1.).contains(&y) {
+        return Err(format!(
+            "{what} [{x}, {y}] is out of range, coordinates go from -1 to 1"
+        ));
+    }
+    Ok(Point2 { x, y })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uses every part of the format
+    const FULL: &str = r#"
+        name = "Everything"
+        size = [0.4, 0.5]
+        shape = { type = "polygon", points = [[0.0, -1.0], [1.0, 1.0], [-1.0, 1.0]] }
+
+        [[obstacles]]
+        type = "pillar"
+        center = [0.0, 0.5]
+        radius = 0.08
+
+        [[obstacles]]
+        type = "wall"
+        from = [-0.3, 0.8]
+        to = [0.3, 0.8]
+
+        [[obstacles]]
+        type = "block"
+        min = [-0.1, 0.1]
+        max = [0.1, 0.2]
+
+        [[spawns]]
+        point = [-0.5, 0.6]
+        heading = 45.0
+
+        [[powermod_zones]]
+        center = [0.0, 0.4]
+        radius = 0.25
+
+        [[portals]]
+        radius = 0.06
+        ends = [
+            { point = [0.5, 0.6], heading = 90.0 },
+            { point = [0.0, -0.4], heading = 270.0 },
+        ]
+
+        [[hazards]]
+        type = "sweeper"
+        pivot = [0.0, 0.3]
+        length = 0.2
+        speed = 45.0
+
+        [[hazards]]
+        type = "orbiter"
+        center = [0.0, 0.5]
+        orbit = 0.3
+        size = 0.05
+        speed = -30.0
+
+        [[hazards]]
+        type = "patrol"
+        path = [[-0.6, 0.9], [0.6, 0.9]]
+        size = 0.04
+        speed = 0.2
+    "#;
+
+    fn invalid(contents: &str) -> String {
+        match Map::parse(contents) {
+            Err(ParseError::Invalid(reason)) => reason,
+            Err(ParseError::Syntax(e)) => panic!("expected an invalid map, got syntax error {e}"),
+            Ok(map) => panic!("expected an invalid map, got {map:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_every_part() {
+        let Ok(map) = Map::parse(FULL) else {
+            panic!("the full map should parse");
+        };
+
+        assert_eq!(map.name, "Everything");
+        assert_eq!(map.size, (0.4, 0.5));
+        assert!(matches!(map.shape, ArenaShape::Polygon(ref points) if points.len() == 3));
+        assert_eq!(map.obstacles.len(), 3);
+        assert_eq!(map.spawns.len(), 1);
+        assert_eq!(map.powermod_zones.len(), 1);
+        assert_eq!(map.portals.len(), 1);
+        assert_eq!(map.hazards.len(), 3);
+    }
+
+    #[test]
+    fn rejects_bad_syntax() {
+        for contents in [
+            "name = ",
+            "name = \"Missing size\"\nsize = [0.5",
+            "name = \"Unknown field\"\nwalls = []",
+            "name = \"Unknown obstacle\"\n[[obstacles]]\ntype = \"spike\"",
+        ] {
+            assert!(
+                matches!(Map::parse(contents), Err(ParseError::Syntax(_))),
+                "{contents:?} should be a syntax error"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_points() {
+        let reason = invalid(
+            r#"
+            name = "Far away"
+            [[obstacles]]
+            type = "wall"
+            from = [0.0, 0.0]
+            to = [1.5, 0.0]
+            "#,
+        );
+
+        assert!(reason.contains("out of range"), "{reason}");
+    }
+
+    #[test]
+    fn rejects_polygons_with_too_few_points() {
+        let reason = invalid(
+            r#"
+            name = "Line"
+            shape = { type = "polygon", points = [[0.0, -1.0], [0.0, 1.0]] }
+            "#,
+        );
+
+        assert!(reason.contains("at least 3 points"), "{reason}");
+    }
+
+    #[test]
+    fn rejects_degenerate_polygons() {
+        for (points, expected) in [
+            (
+                "[[0.0, -1.0], [1.0, 1.0], [0.0, -1.0], [-1.0, 1.0]]",
+                "more than once",
+            ),
+            (
+                "[[-1.0, -1.0], [0.0, -1.0], [1.0, -1.0], [0.0, 1.0]]",
+                "in line",
+            ),
+            ("[[-1.0, 0.0], [0.0, 0.0], [1.0, 0.0]]", "in line"),
+            (
+                "[[-1.0, -1.0], [1.0, 1.0], [1.0, -1.0], [-1.0, 1.0]]",
+                "must not cross",
+            ),
+            (
+                "[[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [0.0, -1.0], [-1.0, 1.0]]",
+                "must not cross",
+            ),
+        ] {
+            let reason = invalid(&format!(
+                "name = \"Broken\"\nshape = {{ type = \"polygon\", points = {points} }}"
+            ));
+            assert!(reason.contains(expected), "{points}: {reason}");
+        }
+    }
+
+    #[test]
+    fn rejects_nan_radii() {
+        for (kind, contents) in [
+            ("pillar", "[[obstacles]]\ntype = \"pillar\"\ncenter = [0.0, 0.0]\nradius = nan"),
+            ("zone", "[[powermod_zones]]\ncenter = [0.0, 0.0]\nradius = nan"),
+            (
+                "portal",
+                "[[portals]]\nradius = nan\nends = [{ point = [-0.5, 0.0] }, { point = [0.5, 0.0] }]",
+            ),
+        ] {
+            let reason = invalid(&format!("name = \"NaN\"\n{contents}"));
+            assert!(reason.contains("must be positive"), "{kind}: {reason}");
+        }
+    }
+
+    #[test]
+    fn rejects_infinite_radii_and_headings() {
+        for (kind, contents, expected) in [
+            (
+                "pillar",
+                "[[obstacles]]\ntype = \"pillar\"\ncenter = [0.0, 0.0]\nradius = inf",
+                "must be positive",
+            ),
+            (
+                "zone",
+                "[[powermod_zones]]\ncenter = [0.0, 0.0]\nradius = inf",
+                "must be positive",
+            ),
+            (
+                "spawn",
+                "[[spawns]]\npoint = [0.0, 0.0]\nheading = nan",
+                "heading must be a number",
+            ),
+            (
+                "portal",
+                "[[portals]]\nradius = 0.06\nends = [{ point = [-0.5, 0.0], heading = -inf }, { point = [0.5, 0.0] }]",
+                "heading must be a number",
+            ),
+        ] {
+            let reason = invalid(&format!("name = \"Infinite\"\n{contents}"));
+            assert!(reason.contains(expected), "{kind}: {reason}");
+        }
+    }
+
+    #[test]
+    fn rejects_overlapping_portals() {
+        let portal = |a: [f32; 2], b: [f32; 2]| {
+            format!(
+                "[[portals]]\nradius = 0.1\nends = [{{ point = {a:?} }}, {{ point = {b:?} }}]\n"
+            )
+        };
+
+        for (portals, expected) in [
+            (portal([0.95, 0.0], [-0.5, 0.0]), "sticks out of the arena"),
+            (
+                portal([0.0, 0.0], [0.15, 0.0]),
+                "overlaps another portal end",
+            ),
+            (
+                portal([0.0, 0.0], [0.5, 0.0]) + &portal([-0.5, 0.0], [0.0, 0.15]),
+                "overlaps a portal end",
+            ),
+        ] {
+            let reason = invalid(&format!("name = \"Crowded\"\n{portals}"));
+            assert!(reason.contains(expected), "{portals}: {reason}");
+        }
+
+        let reason = invalid(&format!(
+            "name = \"Crowded\"\n{}[[spawns]]\npoint = [0.5, 0.05]",
+            portal([0.0, 0.0], [0.5, 0.0])
+        ));
+        assert!(reason.contains("overlaps a portal end"), "{reason}");
+    }
+
+    #[test]
+    fn rejects_spawns_and_portals_in_obstacles() {
+        let pillar = "[[obstacles]]\ntype = \"pillar\"\ncenter = [0.0, 0.0]\nradius = 0.2\n";
+
+        let reason = invalid(&format!(
+            "name = \"Stuck\"\n{pillar}[[spawns]]\npoint = [0.22, 0.0]"
+        ));
+        assert!(reason.contains("away from obstacles"), "{reason}");
+
+        // The center is clear but the portal itself overlaps the pillar
+        let reason = invalid(&format!(
+            "name = \"Stuck\"\n{pillar}[[portals]]\nradius = 0.06\nends = [{{ point = [0.3, 0.0] }}, {{ point = [-0.8, 0.0] }}]"
+        ));
+        assert!(reason.contains("away from obstacles"), "{reason}");
+    }
+
+    #[test]
+    fn rejects_non_finite_hazards() {
+        for (kind, contents, expected) in [
+            (
+                "sweeper speed",
+                "type = \"sweeper\"\npivot = [0.0, 0.0]\nlength = 0.2\nspeed = nan",
+                "must be a number",
+            ),
+            (
+                "sweeper length",
+                "type = \"sweeper\"\npivot = [0.0, 0.0]\nlength = inf\nspeed = 45.0",
+                "must be positive",
+            ),
+            (
+                "orbit",
+                "type = \"orbiter\"\ncenter = [0.0, 0.0]\norbit = inf\nsize = 0.05\nspeed = 30.0",
+                "a number not below zero",
+            ),
+            (
+                "patrol size",
+                "type = \"patrol\"\npath = [[-0.5, 0.0], [0.5, 0.0]]\nsize = inf\nspeed = 0.2",
+                "must be positive",
+            ),
+            (
+                "patrol speed",
+                "type = \"patrol\"\npath = [[-0.5, 0.0], [0.5, 0.0]]\nsize = 0.04\nspeed = -inf",
+                "must be a number",
+            ),
+        ] {
+            let reason = invalid(&format!("name = \"Wild\"\n[[hazards]]\n{contents}"));
+            assert!(reason.contains(expected), "{kind}: {reason}");
+        }
+    }
+
+    #[test]
+    fn rejects_spawns_and_portals_in_reach_of_hazards() {
+        // Sweeps the circle of radius 0.4 around the center
+        let sweeper =
+            "[[hazards]]\ntype = \"sweeper\"\npivot = [0.0, 0.0]\nlength = 0.4\nspeed = 45.0\n";
+
+        let reason = invalid(&format!(
+            "name = \"Swept\"\n{sweeper}[[spawns]]\npoint = [0.0, 0.3]"
+        ));
+        assert!(reason.contains("where hazards move"), "{reason}");
+
+        let reason = invalid(&format!(
+            "name = \"Swept\"\n{sweeper}[[portals]]\nradius = 0.06\nends = [{{ point = [0.45, 0.0] }}, {{ point = [-0.8, 0.0] }}]"
+        ));
+        assert!(reason.contains("where hazards move"), "{reason}");
+    }
+
+    #[test]
+    fn round_trips_through_the_file_format() {
+        let Ok(map) = Map::parse(FULL) else {
+            panic!("the full map should parse");
+        };
+
+        let contents = toml::to_string(&MapFile::from(&map)).expect("the map should serialize");
+
+        match Map::parse(&contents) {
+            Ok(parsed) => assert_eq!(parsed, map, "written as:\n{contents}"),
+            Err(_) => panic!("the written map should parse:\n{contents}"),
+        }
+    }
+
+    #[test]
+    fn bundled_maps_are_valid() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/maps");
+
+        for entry in std::fs::read_dir(dir).expect("the maps directory should exist") {
+            let path = entry.unwrap().path();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let name = path.file_name().unwrap().to_str().unwrap();
+
+            assert!(
+                BUNDLED_MAPS
+                    .iter()
+                    .any(|bundled| bundled.path == format!("/maps/{name}")),
+                "{name} is missing from BUNDLED_MAPS"
+            );
+
+            match Map::parse(&contents) {
+                Ok(_) => {}
+                Err(ParseError::Syntax(e)) => panic!("{}: {e}", path.display()),
+                Err(ParseError::Invalid(reason)) => panic!("{}: {reason}", path.display()),
+            }
+        }
+    }
+}
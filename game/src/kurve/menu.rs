@@ -1,6 +1,6 @@
 use super::curve::{Curve, MoveKeys};
 use super::PAUSE_MENU_CENTER;
-use super::{player::Player, ArenaBounds, Kurve, SETUP_MENU_CENTER};
+use super::{player::Player, ArenaBounds, Kurve, Rules, SETUP_MENU_CENTER};
 use crate::kurve::SIZE_SMALL;
 use crate::{display_key, key_to_str};
 use ggez::GameResult;
@@ -92,7 +92,13 @@ impl KurveMenu {
         }
     }
 
-    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, paused: bool) -> GameResult {
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        paused: bool,
+        rules: &Rules,
+    ) -> GameResult {
         let (x, y) = ctx.gfx.drawable_size();
 
         let center = if paused {
@@ -118,6 +124,25 @@ impl KurveMenu {
                         self.colors.is_empty(),
                     )?;
                 }
+                KurveMenuItem::Wrap => {
+                    let label = if rules.wrap {
+                        "Borders: wrap"
+                    } else {
+                        "Borders: solid"
+                    };
+                    self.draw_rule(ctx, canvas, center, selected, i as f32, label, paused)?;
+                }
+                KurveMenuItem::SuddenDeath => {
+                    let label = match rules.shrink_after {
+                        Some(after) => format!("Sudden death: {}s", after.as_secs()),
+                        None => "Sudden death: off".to_string(),
+                    };
+                    self.draw_rule(ctx, canvas, center, selected, i as f32, &label, paused)?;
+                }
+                KurveMenuItem::Map => {
+                    let label = format!("Map: {}", rules.map.name);
+                    self.draw_rule(ctx, canvas, center, selected, i as f32, &label, paused)?;
+                }
                 KurveMenuItem::Start => {
                     if !paused {
                         self.draw_start_game(ctx, canvas, center, selected)?;
@@ -392,6 +417,65 @@ impl KurveMenu {
         Ok(())
     }
 
+    /// Draw a match rule option. Rules cannot be changed once the match started.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_rule(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        center: Point2<f32>,
+        selected: bool,
+        offset: f32,
+        label: &str,
+        disabled: bool,
+    ) -> GameResult {
+        let (x, y) = ctx.gfx.drawable_size();
+
+        let size = (x * 0.15, y * 0.03);
+
+        let rect = graphics::Rect::new(
+            center.x - size.0 * 0.5,
+            y * 0.3 + offset * 75.,
+            size.0,
+            size.1,
+        );
+
+        let color = if disabled {
+            Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+                a: 0.8,
+            }
+        } else {
+            Color::WHITE
+        };
+
+        let mut text = graphics::Text::new(label);
+        text.set_scale(PxScale::from(24.));
+        text.fragments_mut()
+            .iter_mut()
+            .for_each(|frag| frag.color = Some(color));
+        let text_dims = text.dimensions(ctx).unwrap();
+
+        canvas.draw(
+            &text,
+            DrawParam::default().dest(Point2 {
+                x: rect.x + size.0 * 0.5 - text_dims.w * 0.5,
+                y: rect.y + size.1 * 0.5 - text_dims.h * 0.5,
+            }),
+        );
+
+        if selected {
+            let mesh =
+                graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.), rect, color)?;
+
+            canvas.draw(&mesh, DrawParam::default());
+        }
+
+        Ok(())
+    }
+
     fn draw_start_game(
         &self,
         ctx: &mut Context,
@@ -462,6 +546,15 @@ pub enum SelectAction {
 pub enum KurveMenuItem {
     PlayerCurveConfig(PlayerConfig),
     AddPlayer,
+
+    /// Toggles whether curves wrap around the arena borders
+    Wrap,
+
+    /// Cycles through the round times after which the arena starts shrinking
+    SuddenDeath,
+
+    /// Cycles through the maps loaded from the maps directory
+    Map,
     Start,
 }
 
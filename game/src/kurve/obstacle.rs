@@ -0,0 +1,98 @@
+use super::{
+    point::{Girth, Line},
+    shape::{edges, polygon_contains, segment_distance},
+    ArenaBounds,
+};
+use ggez::mint::Point2;
+use std::f32::consts::PI;
+
+/// How thick the borders of obstacles are
+pub const OBSTACLE_GIRTH: Girth = Girth::Large;
+
+/// How many segments approximate the outline of a pillar
+const PILLAR_SEGMENTS: usize = 24;
+
+/// A static piece of the arena killing curves on contact.
+///
+/// Coordinates are in arena space, see [ArenaBounds::place].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Obstacle {
+    /// A straight wall between two points
+    Wall { from: Point2<f32>, to: Point2<f32> },
+
+    /// A round pillar
+    Pillar { center: Point2<f32>, radius: f32 },
+
+    /// A rectangular block between two opposite corners
+    Block { min: Point2<f32>, max: Point2<f32> },
+}
+
+impl Obstacle {
+    /// Whether the outline of the obstacle encloses an area
+    #[inline]
+    pub fn closed(&self) -> bool {
+        !matches!(self, Obstacle::Wall { .. })
+    }
+
+    /// The border of the obstacle in screen coordinates. Loops back to the first point if the
+    /// obstacle is [closed](Self::closed).
+    pub fn outline(&self, bounds: ArenaBounds) -> Vec<Point2<f32>> {
+        let place = |x, y| bounds.place(Point2 { x, y });
+
+        match self {
+            Obstacle::Wall { from, to } => vec![place(from.x, from.y), place(to.x, to.y)],
+            Obstacle::Pillar { center, radius } => (0..PILLAR_SEGMENTS)
+                .map(|i| {
+                    let angle = 2. * PI * i as f32 / PILLAR_SEGMENTS as f32;
+                    place(
+                        center.x + radius * angle.cos(),
+                        center.y + radius * angle.sin(),
+                    )
+                })
+                .collect(),
+            Obstacle::Block { min, max } => vec![
+                place(min.x, min.y),
+                place(max.x, min.y),
+                place(max.x, max.y),
+                place(min.x, max.y),
+            ],
+        }
+    }
+
+    /// The border of the obstacle as lines curves collide with
+    pub fn lines(&self, bounds: ArenaBounds) -> Vec<Line> {
+        let outline = self.outline(bounds);
+
+        let segments = if self.closed() {
+            outline.len()
+        } else {
+            outline.len() - 1
+        };
+
+        edges(&outline)
+            .take(segments)
+            .map(|(a, b)| Line::interpolate(a, b, OBSTACLE_GIRTH))
+            .collect()
+    }
+
+    /// Distance from the point to the edge of the obstacle, zero if the point is inside
+    #[inline]
+    pub fn distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        (self.outline_distance(bounds, point) - OBSTACLE_GIRTH.as_f32()).max(0.)
+    }
+
+    /// Distance from the point to the outline of the obstacle, zero if the point is inside.
+    /// Unlike [distance](Self::distance) it leaves out the thickness of the border, which is
+    /// only known in screen coordinates.
+    pub fn outline_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        let outline = self.outline(bounds);
+
+        if self.closed() && polygon_contains(&outline, point) {
+            return 0.;
+        }
+
+        edges(&outline)
+            .map(|(a, b)| segment_distance(point, a, b))
+            .fold(f32::INFINITY, f32::min)
+    }
+}
@@ -11,10 +11,11 @@ use ggez::{
 
 use super::curve::Curve;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Hash)]
 pub enum Girth {
     Tiny,
     Small,
+    #[default]
     Normal,
     Large,
     Larger,
@@ -68,6 +69,20 @@ impl Girth {
             G::Chungus => G::Larger,
         }
     }
+
+    /// Increment or decrement the girth `steps` times, stopping at the min and max
+    #[inline]
+    pub fn step(&self, steps: i32) -> Self {
+        let mut girth = *self;
+        for _ in 0..steps.unsigned_abs() {
+            girth = if steps > 0 {
+                girth.increment()
+            } else {
+                girth.decrement()
+            };
+        }
+        girth
+    }
 }
 
 impl<T> Index<Girth> for [T; 6] {
@@ -98,12 +113,6 @@ impl<T> IndexMut<Girth> for [T; 6] {
     }
 }
 
-impl Default for Girth {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// A line obtained from interpolating 2 points.
 #[derive(Debug, Clone)]
 pub struct Line {
@@ -261,11 +270,6 @@ impl BoundingBox {
         self.0[8].x -= amount;
     } */
 
-    /// Return the bounding box as polygon points for drawing (without the center point)
-    pub fn as_polygon(&self) -> &[Point2<f32>] {
-        &self.0[1..]
-    }
-
     pub fn iter(&self) -> std::slice::Iter<'_, Point2<f32>> {
         self.0.iter()
     }
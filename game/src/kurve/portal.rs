@@ -0,0 +1,93 @@
+use super::{curve::Curve, ArenaBounds};
+use ggez::{graphics::Color, mint::Point2};
+use std::time::Duration;
+
+/// How long a curve coming out of a portal ignores trails and other portals
+pub const PORTAL_GRACE: Duration = Duration::from_millis(400);
+
+/// Colors telling portal pairs apart, repeated when a map has more pairs
+const PORTAL_COLORS: [Color; 4] = [
+    Color {
+        r: 0.2,
+        g: 0.6,
+        b: 1.,
+        a: 1.,
+    },
+    Color {
+        r: 1.,
+        g: 0.55,
+        b: 0.1,
+        a: 1.,
+    },
+    Color {
+        r: 0.7,
+        g: 0.3,
+        b: 1.,
+        a: 1.,
+    },
+    Color {
+        r: 0.2,
+        g: 0.9,
+        b: 0.6,
+        a: 1.,
+    },
+];
+
+/// A pair of round gates moving curves entering one of them to the other.
+///
+/// Coordinates and the radius are in arena space, see [ArenaBounds::place].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portal {
+    pub ends: [PortalEnd; 2],
+    pub radius: f32,
+}
+
+/// One side of a [Portal]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortalEnd {
+    pub center: Point2<f32>,
+
+    /// Which way the end faces in radians. A curve coming out heads this way if it entered the
+    /// other end heading the way that one faces.
+    pub rotation: f32,
+}
+
+impl Portal {
+    /// The color of the `index`th portal of a map
+    #[inline]
+    pub fn color(index: usize) -> Color {
+        PORTAL_COLORS[index % PORTAL_COLORS.len()]
+    }
+
+    /// The radius in screen coordinates
+    #[inline]
+    pub fn screen_radius(&self, bounds: ArenaBounds) -> f32 {
+        self.radius * bounds.inscribed().1
+    }
+
+    /// The index of the end the point is in, if any
+    pub fn entered(&self, bounds: ArenaBounds, point: Point2<f32>) -> Option<usize> {
+        let radius = self.screen_radius(bounds);
+
+        self.ends
+            .iter()
+            .position(|end| super::distance(bounds.place(end.center), point) < radius)
+    }
+
+    /// Move the curve from the end at index `from` to just outside the other end. The heading
+    /// relative to the end it entered is kept relative to the end it comes out of.
+    pub fn teleport(&self, bounds: ArenaBounds, curve: &mut Curve, from: usize) {
+        let (entry, exit) = (self.ends[from], self.ends[1 - from]);
+
+        curve.rotation = exit.rotation + (curve.rotation - entry.rotation);
+
+        // Come out past the edge so the curve does not fall right back in once the grace is over
+        let distance = self.screen_radius(bounds) + curve.girth.as_f32() + 1.;
+        let center = bounds.place(exit.center);
+
+        curve.position = Point2 {
+            x: center.x + distance * curve.rotation.cos(),
+            y: center.y + distance * curve.rotation.sin(),
+        };
+    }
+}
@@ -1,168 +1,476 @@
 use std::{
-    fmt::Display,
+    fmt::Debug,
     time::{Duration, Instant},
 };
 
-use ggez::{graphics::Color, mint::Point2, Context, GameResult};
+use ggez::{glam, graphics::Color, mint::Point2, Context, GameResult};
+use rand::Rng;
 
-use super::{
-    curve::Curve,
-    point::{BoundingCircle, Girth},
-    POWERMOD_SIZE,
+use super::{curve::Curve, ArenaMode, POWERMOD_DURATION, POWERMOD_SIZE, SPAWN_TELEGRAPH_DURATION};
+use crate::resource::Resource;
+
+mod anorexia;
+mod bomb;
+mod chungus;
+mod eraser;
+mod freeze;
+mod invulnerability;
+mod magnet;
+mod reverse;
+mod right_angle;
+mod rot_down;
+mod rot_up;
+mod shield;
+mod speed_down;
+mod speed_up;
+mod swap;
+mod wall_pass;
+
+pub use reverse::Reverse;
+
+/// Velocity added or removed by the speed powermods
+const VELO: f32 = 10.;
+
+/// Rotation speed added or removed by the rotation powermods
+const ROTUP: f32 = 0.01;
+
+/// Default scale of powermod icons
+const ICON_SCALE: f32 = 0.07;
+
+/// Background color of powermods affecting the curve that picks them up
+const COLLECTOR_COLOR: Color = Color {
+    r: 0.50,
+    g: 0.79,
+    b: 0.64,
+    a: 1.,
 };
 
+/// Background color of powermods affecting everyone except the curve that picks them up
+const OPPONENTS_COLOR: Color = Color {
+    r: 0.85,
+    g: 0.25,
+    b: 0.25,
+    a: 1.,
+};
+
+/// Background color of powermods affecting every curve
+const EVERYONE_COLOR: Color = Color {
+    r: 0.30,
+    g: 0.50,
+    b: 0.90,
+    a: 1.,
+};
+
+/// A modification applied to curves when they pick up a powermod. Every modifier lives in its own
+/// file in this module and is made available for spawning in the [PowerRegistry].
+pub trait PowerModifier: Debug + Sync {
+    /// Name displayed to the players
+    fn name(&self) -> &'static str;
+
+    /// Which curves get modified when the powermod is picked up, unless the registry
+    /// overrides it
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Collector
+    }
+
+    /// Whether dead curves get modified as well. Only makes sense for modifiers that act on what
+    /// a curve left behind instead of the curve itself.
+    fn affects_dead(&self) -> bool {
+        false
+    }
+
+    /// Whether the modification is used up to survive a fatal collision. The first such
+    /// modification on a curve is removed when the curve collides instead of killing it.
+    fn absorbs_collision(&self) -> bool {
+        false
+    }
+
+    /// How long the modification lasts before it is removed
+    fn duration(&self) -> Duration {
+        POWERMOD_DURATION
+    }
+
+    /// The icon drawn on the powermod
+    fn icon(&self) -> Resource;
+
+    /// The scale the icon is drawn at
+    fn icon_scale(&self) -> glam::Vec2 {
+        glam::vec2(ICON_SCALE, ICON_SCALE)
+    }
+
+    /// The sound played when the powermod is picked up
+    fn sound(&self) -> Resource;
+
+    /// Modify the curves as a whole, once per pickup and before [apply][Self::apply] runs
+    /// on each affected curve. Only modifiers acting on multiple curves at once need to override
+    /// this.
+    fn apply_to_curves(
+        &self,
+        _ctx: &mut Context,
+        _collector: usize,
+        _curves: &mut [Curve],
+    ) -> GameResult {
+        Ok(())
+    }
+
+    /// Modify the curve. `point` is where the powermod was picked up.
+    fn apply(&self, ctx: &mut Context, curve: &mut Curve, point: Point2<f32>) -> GameResult;
+
+    /// Reverse the modification done in [apply][Self::apply]
+    fn remove(&self, ctx: &mut Context, curve: &mut Curve) -> GameResult;
+}
+
+/// The curves a powermod is applied to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerTarget {
+    /// Only the curve that picked it up
+    Collector,
+
+    /// Every curve except the one that picked it up
+    Opponents,
+
+    /// Every curve
+    Everyone,
+}
+
+impl PowerTarget {
+    /// Whether the curve at index `curve` is affected when `collector` picks up the powermod
+    #[inline]
+    pub fn affects(&self, collector: usize, curve: usize) -> bool {
+        match self {
+            PowerTarget::Collector => collector == curve,
+            PowerTarget::Opponents => collector != curve,
+            PowerTarget::Everyone => true,
+        }
+    }
+
+    /// Powermods are colored according to who they affect
+    #[inline]
+    pub fn color(&self) -> Color {
+        match self {
+            PowerTarget::Collector => COLLECTOR_COLOR,
+            PowerTarget::Opponents => OPPONENTS_COLOR,
+            PowerTarget::Everyone => EVERYONE_COLOR,
+        }
+    }
+}
+
 /// Modifies the curve in some way
 #[derive(Debug)]
 pub struct PowerMod {
     pub point: Point2<f32>,
-    pub ty: PowerModifier,
-    pub bbox: BoundingCircle,
+    pub ty: &'static dyn PowerModifier,
+    pub target: PowerTarget,
+
+    /// When the powermod appeared in the arena
+    pub spawned: Instant,
+
+    /// How fast the powermod is drifting in pixels per second. Powermods stand still unless
+    /// something pulls them.
+    pub velocity: glam::Vec2,
 }
 
 impl PowerMod {
     #[inline]
-    pub fn new(point: Point2<f32>, ty: PowerModifier) -> Self {
+    pub fn new(point: Point2<f32>, entry: PowerEntry) -> Self {
         Self {
             point,
-            ty,
-            bbox: BoundingCircle::new(point, POWERMOD_SIZE),
+            ty: entry.modifier,
+            target: entry.target,
+            spawned: Instant::now(),
+            velocity: glam::Vec2::ZERO,
         }
     }
 
+    /// Move the powermod according to its velocity
+    #[inline]
+    pub fn mv(&mut self, delta: f32) {
+        self.point.x += self.velocity.x * delta;
+        self.point.y += self.velocity.y * delta;
+    }
+
+    /// Powermods can only be picked up once their spawn telegraph is over
+    #[inline]
+    pub fn collectible(&self, now: Instant) -> bool {
+        now.duration_since(self.spawned) >= SPAWN_TELEGRAPH_DURATION
+    }
+
+    /// How far along the spawn telegraph is in the range `0.0..=1.0`
+    #[inline]
+    pub fn spawn_progress(&self, now: Instant) -> f32 {
+        (now.duration_since(self.spawned).as_secs_f32() / SPAWN_TELEGRAPH_DURATION.as_secs_f32())
+            .min(1.)
+    }
+
     #[inline]
     pub fn color(&self) -> Color {
-        let green = Color {
-            r: 0.50,
-            g: 0.79,
-            b: 0.64,
-            a: 1.,
-        };
-        match self.ty {
-            PowerModifier::SpeedUp => green,
-            PowerModifier::RotUp => green,
-            PowerModifier::Invulnerability => green,
-            PowerModifier::Anorexia => green,
-            PowerModifier::SpeedDown => Color::RED,
-            PowerModifier::RotDown => Color::RED,
-            PowerModifier::Chungus => Color::RED,
-        }
+        self.target.color()
     }
 
-    pub fn bounds(&self) -> (f32, f32, f32, f32) {
-        (
-            self.point.x - POWERMOD_SIZE * 0.5,
-            self.point.x + POWERMOD_SIZE * 0.5,
-            self.point.y - POWERMOD_SIZE * 0.5,
-            self.point.y - POWERMOD_SIZE * 0.5,
-        )
+    /// Whether a curve head at `head` with the given girth touches the powermod. Both are
+    /// circles, so they touch when their centers are closer than the sum of their radii.
+    #[inline]
+    pub fn hit(&self, head: Point2<f32>, girth: f32) -> bool {
+        let (d_x, d_y) = (head.x - self.point.x, head.y - self.point.y);
+        let reach = POWERMOD_SIZE + girth;
+        d_x * d_x + d_y * d_y <= reach * reach
     }
 }
 
-/// Reverses any modification caused by a powerup
+/// Reverses any modification caused by a powerup. Every curve affected by a powermod
+/// gets its own timeout.
 #[derive(Debug)]
 pub struct PowerTimeout {
+    /// The affected curve
     pub curve: usize,
+
+    /// Who the powermod targeted
+    pub target: PowerTarget,
+
     pub started: Instant,
-    pub ty: PowerModifier,
+    pub ty: &'static dyn PowerModifier,
 }
 
-/// All possible variations for a power up/down.
-#[repr(usize)]
-#[derive(Debug, Clone, Copy)]
-pub enum PowerModifier {
-    // Good
-    /// Increases velocity
-    SpeedUp,
+impl PowerTimeout {
+    #[inline]
+    pub fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.started) >= self.ty.duration()
+    }
+
+    /// How much of the effect is left in the range `0.0..=1.0`
+    #[inline]
+    pub fn remaining(&self, now: Instant) -> f32 {
+        let duration = self.ty.duration();
 
-    /// Increases rotation speed
-    RotUp,
+        if duration.is_zero() {
+            return 0.;
+        }
 
-    /// Makes the curve invulnerable
-    Invulnerability,
+        1. - (now.duration_since(self.started).as_secs_f32() / duration.as_secs_f32()).min(1.)
+    }
+}
 
-    /// Makes the curve thinner
-    Anorexia,
+/// A modifier available for spawning
+#[derive(Debug, Clone, Copy)]
+pub struct PowerEntry {
+    pub modifier: &'static dyn PowerModifier,
 
-    // Bad
-    /// Decreases player velocity
-    SpeedDown,
+    /// Who the modifier is applied to when picked up
+    pub target: PowerTarget,
 
-    // Decreases rotation speed
-    RotDown,
+    /// How likely the modifier is to spawn relative to the other entries
+    pub weight: u32,
 
-    // Constricts rotation to right angles
-    // RightAngle,
-    /// Makes the curve fatter
-    Chungus,
+    /// Arena modes the modifier can spawn in
+    pub modes: &'static [ArenaMode],
 }
 
-const ROTUP: f32 = 0.01;
-const VELO: f32 = 10.;
+/// Holds all the modifiers powermods can spawn with
+#[derive(Debug)]
+pub struct PowerRegistry {
+    entries: Vec<PowerEntry>,
+}
 
-impl PowerModifier {
-    pub fn apply(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
-        match self {
-            PowerModifier::SpeedUp => curve.velocity += VELO,
-            PowerModifier::RotUp => curve.rotation_speed += ROTUP,
-            PowerModifier::Invulnerability => {
-                curve.trail_active = false;
-                curve.trail_ts = Instant::now();
-                curve.trail_fuse = Duration::MAX;
-            }
-            PowerModifier::SpeedDown => curve.velocity -= VELO,
-            PowerModifier::RotDown => curve.rotation_speed -= ROTUP,
-            // PowerModifier::RightAngle => todo!(),
-            PowerModifier::Anorexia => {
-                if curve.girth > Girth::min() {
-                    curve.girth = curve.girth.decrement();
-                }
-            }
-            PowerModifier::Chungus => {
-                if curve.girth < Girth::max() {
-                    curve.girth = curve.girth.increment();
-                }
-            }
+impl PowerRegistry {
+    /// Create a registry without any modifiers
+    pub fn empty() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Make the modifier available for spawning with its default target.
+    /// Modifiers with a weight of 0 never spawn.
+    pub fn register(self, modifier: &'static dyn PowerModifier, weight: u32) -> Self {
+        self.register_targeted(modifier, modifier.target(), weight)
+    }
+
+    /// Make the modifier available for spawning with the given target. The same modifier can be
+    /// registered multiple times with different targets.
+    pub fn register_targeted(
+        mut self,
+        modifier: &'static dyn PowerModifier,
+        target: PowerTarget,
+        weight: u32,
+    ) -> Self {
+        self.entries.push(PowerEntry {
+            modifier,
+            target,
+            weight,
+            modes: &[ArenaMode::Solid, ArenaMode::Wrap],
+        });
+        self
+    }
+
+    /// Restrict the most recently registered entry to the given arena modes
+    pub fn only_in(mut self, modes: &'static [ArenaMode]) -> Self {
+        if let Some(entry) = self.entries.last_mut() {
+            entry.modes = modes;
         }
-        Ok(())
+        self
     }
 
-    pub fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
-        match self {
-            PowerModifier::SpeedUp => {
-                if curve.velocity > VELO {
-                    curve.velocity -= VELO
-                }
+    /// Pick a random entry enabled in the mode according to the entry weights
+    pub fn random(&self, mode: ArenaMode) -> Option<PowerEntry> {
+        let entries = || {
+            self.entries
+                .iter()
+                .filter(move |entry| entry.modes.contains(&mode))
+        };
+
+        let total = entries().map(|entry| entry.weight).sum::<u32>();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rand::thread_rng().gen_range(0..total);
+
+        for entry in entries() {
+            if roll < entry.weight {
+                return Some(*entry);
             }
-            PowerModifier::RotUp => curve.rotation_speed -= ROTUP,
-            PowerModifier::Invulnerability => {
-                curve.trail_active = true;
-                curve.trail_ts = Instant::now();
-                curve.trail_fuse = Curve::new_trail_fuse();
+            roll -= entry.weight;
+        }
+
+        None
+    }
+
+    /// Every registered modifier, once for each time it was registered
+    pub fn modifiers(&self) -> impl Iterator<Item = &'static dyn PowerModifier> + '_ {
+        self.entries.iter().map(|entry| entry.modifier)
+    }
+}
+
+impl Default for PowerRegistry {
+    /// A registry with all the built-in modifiers
+    fn default() -> Self {
+        Self::empty()
+            .register(&speed_up::SpeedUp, 10)
+            .register_targeted(&speed_up::SpeedUp, PowerTarget::Opponents, 6)
+            .register_targeted(&speed_up::SpeedUp, PowerTarget::Everyone, 4)
+            .register(&speed_down::SpeedDown, 10)
+            .register_targeted(&speed_down::SpeedDown, PowerTarget::Collector, 6)
+            .register(&rot_up::RotUp, 8)
+            .register(&rot_down::RotDown, 8)
+            .register(&anorexia::Anorexia, 10)
+            .register(&chungus::Chungus, 10)
+            .register(&invulnerability::Invulnerability, 5)
+            .register(&right_angle::RightAngle, 6)
+            .register(&wall_pass::WallPass, 6)
+            // Borders are already passable when wrapping
+            .only_in(&[ArenaMode::Solid])
+            .register(&eraser::Eraser, 3)
+            .register(&bomb::Bomb, 5)
+            .register(&Reverse, 6)
+            .register(&shield::Shield, 5)
+            .register(&freeze::Freeze, 4)
+            .register(&swap::Swap, 4)
+            .register(&magnet::Magnet, 5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn powermod_at(x: f32, y: f32) -> PowerMod {
+        PowerMod::new(
+            Point2 { x, y },
+            PowerEntry {
+                modifier: &speed_up::SpeedUp,
+                target: PowerTarget::Collector,
+                weight: 1,
+                modes: &[ArenaMode::Solid],
+            },
+        )
+    }
+
+    /// Points on a circle around the center at `distance`, one for every 15 degrees
+    fn around(center: Point2<f32>, distance: f32) -> impl Iterator<Item = (f32, Point2<f32>)> {
+        (0..24).map(move |i| {
+            let angle = i as f32 * PI / 12.;
+            (
+                angle,
+                Point2 {
+                    x: center.x + distance * angle.cos(),
+                    y: center.y + distance * angle.sin(),
+                },
+            )
+        })
+    }
+
+    #[test]
+    fn hits_when_touching_from_every_direction() {
+        let powermod = powermod_at(200., 150.);
+
+        for girth in [1., 2., 8.] {
+            let reach = POWERMOD_SIZE + girth;
+
+            for (angle, head) in around(powermod.point, reach - 0.01) {
+                assert!(
+                    powermod.hit(head, girth),
+                    "girth {girth} at angle {angle} should hit"
+                );
             }
-            PowerModifier::Anorexia => {
-                curve.girth = curve.girth.increment();
+
+            for (angle, head) in around(powermod.point, reach + 0.01) {
+                assert!(
+                    !powermod.hit(head, girth),
+                    "girth {girth} at angle {angle} should miss"
+                );
             }
-            PowerModifier::SpeedDown => curve.velocity += VELO,
-            PowerModifier::RotDown => curve.rotation_speed += ROTUP,
-            // PowerModifier::RightAngle => todo!(),
-            PowerModifier::Chungus => {
-                curve.girth = curve.girth.decrement();
+        }
+    }
+
+    #[test]
+    fn approaching_curve_hits_at_the_same_distance_from_every_direction() {
+        let powermod = powermod_at(-40., 75.);
+        let girth = 2.;
+
+        for (angle, start) in around(powermod.point, 100.) {
+            let step = Point2 {
+                x: -angle.cos() * 0.5,
+                y: -angle.sin() * 0.5,
+            };
+
+            let mut head = start;
+            let mut travelled = 0.;
+
+            while !powermod.hit(head, girth) {
+                head.x += step.x;
+                head.y += step.y;
+                travelled += 0.5;
+                assert!(travelled <= 100., "angle {angle} never hit");
             }
+
+            let distance =
+                ((head.x - powermod.point.x).powi(2) + (head.y - powermod.point.y).powi(2)).sqrt();
+            let reach = POWERMOD_SIZE + girth;
+
+            assert!(
+                distance <= reach && distance > reach - 0.5,
+                "angle {angle} hit at distance {distance}"
+            );
         }
-        Ok(())
     }
-}
 
-impl Display for PowerModifier {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PowerModifier::SpeedUp => write!(f, "SpeedUp"),
-            PowerModifier::RotUp => write!(f, "RotUp"),
-            PowerModifier::Invulnerability => write!(f, "Invul"),
-            PowerModifier::Anorexia => write!(f, "Thin"),
-            PowerModifier::SpeedDown => write!(f, "SpeedDown"),
-            PowerModifier::RotDown => write!(f, "RotDown"),
-            PowerModifier::Chungus => write!(f, "Fat"),
+    #[test]
+    fn passing_by_misses() {
+        let powermod = powermod_at(0., 0.);
+        let girth = 2.;
+        let offset = POWERMOD_SIZE + girth + 1.;
+
+        // Move along horizontal and vertical lines just outside the reach
+        for i in -100..=100 {
+            let t = i as f32;
+            for head in [
+                Point2 { x: t, y: offset },
+                Point2 { x: t, y: -offset },
+                Point2 { x: offset, y: t },
+                Point2 { x: -offset, y: t },
+            ] {
+                assert!(!powermod.hit(head, girth), "{head:?} should miss");
+            }
         }
     }
 }
@@ -0,0 +1,32 @@
+use super::PowerModifier;
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Makes the curve thinner
+#[derive(Debug)]
+pub struct Anorexia;
+
+impl PowerModifier for Anorexia {
+    fn name(&self) -> &'static str {
+        "Thin"
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("earth-worm.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/anorexia.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Girth(-1));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Girth(-1));
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::Curve;
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use std::time::Duration;
+
+/// Radius around the pickup point in which trails get erased
+const BOMB_RADIUS: f32 = 70.;
+
+/// Erases the trail of every curve around the point it was picked up at
+#[derive(Debug)]
+pub struct Bomb;
+
+impl PowerModifier for Bomb {
+    fn name(&self) -> &'static str {
+        "Bomb"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Everyone
+    }
+
+    fn affects_dead(&self) -> bool {
+        true
+    }
+
+    /// The trail is gone for good, there is nothing to wait for
+    fn duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("bomb.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/bomb.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, point: Point2<f32>) -> GameResult {
+        curve.trail.erase_circle(point, BOMB_RADIUS);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, _curve: &mut Curve) -> GameResult {
+        Ok(())
+    }
+}
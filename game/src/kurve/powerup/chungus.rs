@@ -0,0 +1,36 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Makes the curve fatter
+#[derive(Debug)]
+pub struct Chungus;
+
+impl PowerModifier for Chungus {
+    fn name(&self) -> &'static str {
+        "Fat"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("boar.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/chungus.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Girth(1));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Girth(1));
+        Ok(())
+    }
+}
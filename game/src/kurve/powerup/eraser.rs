@@ -0,0 +1,45 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::Curve;
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use std::time::Duration;
+
+/// Wipes the whole trail of every curve
+#[derive(Debug)]
+pub struct Eraser;
+
+impl PowerModifier for Eraser {
+    fn name(&self) -> &'static str {
+        "Eraser"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Everyone
+    }
+
+    fn affects_dead(&self) -> bool {
+        true
+    }
+
+    /// The trail is gone for good, there is nothing to wait for
+    fn duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("eraser.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/eraser.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.trail.clear();
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, _curve: &mut Curve) -> GameResult {
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::Curve;
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use std::time::Duration;
+
+/// Stops the curve from moving, steering and drawing its trail
+#[derive(Debug)]
+pub struct Freeze;
+
+impl PowerModifier for Freeze {
+    fn name(&self) -> &'static str {
+        "Freeze"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs(3)
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("freeze.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/freeze.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.freeze();
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.thaw();
+        Ok(())
+    }
+}
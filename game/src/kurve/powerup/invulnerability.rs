@@ -0,0 +1,45 @@
+use super::PowerModifier;
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use std::time::{Duration, Instant};
+
+/// Makes the curve invulnerable
+#[derive(Debug)]
+pub struct Invulnerability;
+
+impl PowerModifier for Invulnerability {
+    fn name(&self) -> &'static str {
+        "Invul"
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("evil-wings.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/invuln.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Invulnerability);
+        curve.trail_active = false;
+        curve.trail_ts = Instant::now();
+        curve.trail_fuse = Duration::MAX;
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Invulnerability);
+
+        // Another invulnerability is still running
+        if curve.has_effect(Effect::Invulnerability) {
+            return Ok(());
+        }
+
+        curve.trail_active = true;
+        curve.trail_ts = Instant::now();
+        curve.trail_fuse = Curve::new_trail_fuse();
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use super::PowerModifier;
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use std::time::Duration;
+
+/// Pulls nearby powermods towards the curve
+#[derive(Debug)]
+pub struct Magnet;
+
+impl PowerModifier for Magnet {
+    fn name(&self) -> &'static str {
+        "Magnet"
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs(12)
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("magnet.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/magnet.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Magnet);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Magnet);
+        Ok(())
+    }
+}
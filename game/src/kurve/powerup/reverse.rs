@@ -0,0 +1,36 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Swaps the curve's move keys so it turns the opposite way
+#[derive(Debug)]
+pub struct Reverse;
+
+impl PowerModifier for Reverse {
+    fn name(&self) -> &'static str {
+        "Reverse"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("reverse.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/reverse.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Reverse);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Reverse);
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use super::{PowerModifier, PowerTarget};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Makes the curve turn only in right angles
+#[derive(Debug)]
+pub struct RightAngle;
+
+impl PowerModifier for RightAngle {
+    fn name(&self) -> &'static str {
+        "RightAngle"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("right-angle.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/right_angle.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::RightAngle);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::RightAngle);
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use super::{PowerModifier, PowerTarget, ROTUP};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Decreases rotation speed
+#[derive(Debug)]
+pub struct RotDown;
+
+impl PowerModifier for RotDown {
+    fn name(&self) -> &'static str {
+        "RotDown"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("battery-minus.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/rot_down.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::RotationSpeed(-ROTUP));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::RotationSpeed(-ROTUP));
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use super::{PowerModifier, ROTUP};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Increases rotation speed
+#[derive(Debug)]
+pub struct RotUp;
+
+impl PowerModifier for RotUp {
+    fn name(&self) -> &'static str {
+        "RotUp"
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("battery-plus.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/rot_up.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::RotationSpeed(ROTUP));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::RotationSpeed(ROTUP));
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use super::PowerModifier;
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Lets the curve survive a single collision
+#[derive(Debug)]
+pub struct Shield;
+
+impl PowerModifier for Shield {
+    fn name(&self) -> &'static str {
+        "Shield"
+    }
+
+    fn absorbs_collision(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("shield.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/shield.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Shield);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Shield);
+        Ok(())
+    }
+}
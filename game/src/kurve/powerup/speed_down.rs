@@ -0,0 +1,36 @@
+use super::{PowerModifier, PowerTarget, VELO};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Decreases velocity
+#[derive(Debug)]
+pub struct SpeedDown;
+
+impl PowerModifier for SpeedDown {
+    fn name(&self) -> &'static str {
+        "SpeedDown"
+    }
+
+    fn target(&self) -> PowerTarget {
+        PowerTarget::Opponents
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("turtle.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/speed_down.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Velocity(-VELO));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Velocity(-VELO));
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use super::{PowerModifier, VELO};
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Increases velocity
+#[derive(Debug)]
+pub struct SpeedUp;
+
+impl PowerModifier for SpeedUp {
+    fn name(&self) -> &'static str {
+        "SpeedUp"
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("sprint.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/speed_up.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::Velocity(VELO));
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::Velocity(VELO));
+        Ok(())
+    }
+}
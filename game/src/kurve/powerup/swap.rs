@@ -0,0 +1,69 @@
+use super::PowerModifier;
+use crate::kurve::curve::Curve;
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+use rand::seq::IteratorRandom;
+use std::time::Duration;
+
+/// Swaps the collector's position and heading with a random opponent
+#[derive(Debug)]
+pub struct Swap;
+
+impl PowerModifier for Swap {
+    fn name(&self) -> &'static str {
+        "Swap"
+    }
+
+    /// The swap happens instantly
+    fn duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("swap.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/swap.wav")
+    }
+
+    /// Only the heads move, the trails stay where they are. Both curves briefly stop drawing
+    /// their trail so no line is drawn across the arena and neither dies instantly in their
+    /// new surroundings.
+    fn apply_to_curves(
+        &self,
+        _ctx: &mut Context,
+        collector: usize,
+        curves: &mut [Curve],
+    ) -> GameResult {
+        let Some(opponent) = curves
+            .iter()
+            .enumerate()
+            .filter(|(i, curve)| *i != collector && curve.alive)
+            .map(|(i, _)| i)
+            .choose(&mut rand::thread_rng())
+        else {
+            return Ok(());
+        };
+
+        let (position, rotation) = (curves[collector].position, curves[collector].rotation);
+
+        curves[collector].position = curves[opponent].position;
+        curves[collector].rotation = curves[opponent].rotation;
+        curves[opponent].position = position;
+        curves[opponent].rotation = rotation;
+
+        curves[collector].skip_trail();
+        curves[opponent].skip_trail();
+
+        Ok(())
+    }
+
+    fn apply(&self, _ctx: &mut Context, _curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, _curve: &mut Curve) -> GameResult {
+        Ok(())
+    }
+}
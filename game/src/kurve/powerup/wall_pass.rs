@@ -0,0 +1,32 @@
+use super::PowerModifier;
+use crate::kurve::curve::{Curve, Effect};
+use crate::{resource, resource::Resource};
+use ggez::{mint::Point2, Context, GameResult};
+
+/// Lets the curve pass through the arena borders
+#[derive(Debug)]
+pub struct WallPass;
+
+impl PowerModifier for WallPass {
+    fn name(&self) -> &'static str {
+        "WallPass"
+    }
+
+    fn icon(&self) -> Resource {
+        resource!("wall-pass.png")
+    }
+
+    fn sound(&self) -> Resource {
+        resource!("sfx/wall_pass.wav")
+    }
+
+    fn apply(&self, _ctx: &mut Context, curve: &mut Curve, _point: Point2<f32>) -> GameResult {
+        curve.add_effect(Effect::WallPass);
+        Ok(())
+    }
+
+    fn remove(&self, _ctx: &mut Context, curve: &mut Curve) -> GameResult {
+        curve.remove_effect(Effect::WallPass);
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+use super::{curve::Curve, point::BoundingBox, ArenaBounds};
+use ggez::mint::Point2;
+use std::f32::consts::PI;
+
+/// How many segments approximate the outline of a circular arena
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// The shape of the playing field within its [ArenaBounds].
+///
+/// Circles and polygons keep their aspect ratio and are fit into the circle returned by
+/// [ArenaBounds::inscribed].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum ArenaShape {
+    /// Fills the whole bounds
+    #[default]
+    Rectangle,
+
+    Circle,
+
+    /// A closed polygon, convex or concave. Vertices are in arena space, see
+    /// [ArenaBounds::place], and edges must not cross each other.
+    Polygon(Vec<Point2<f32>>),
+}
+
+impl ArenaShape {
+    /// A polygon with `sides` equal sides, with a vertex pointing up
+    pub fn regular(sides: usize) -> Self {
+        let points = (0..sides)
+            .map(|i| {
+                let angle = -PI * 0.5 + 2. * PI * i as f32 / sides as f32;
+                Point2 {
+                    x: angle.cos(),
+                    y: angle.sin(),
+                }
+            })
+            .collect();
+
+        Self::Polygon(points)
+    }
+
+    /// The border of the arena as a closed loop of points in screen coordinates
+    pub fn outline(&self, bounds: ArenaBounds) -> Vec<Point2<f32>> {
+        let (center, radius) = bounds.inscribed();
+
+        match self {
+            ArenaShape::Rectangle => vec![
+                Point2 {
+                    x: bounds.x_min,
+                    y: bounds.y_min,
+                },
+                Point2 {
+                    x: bounds.x_max,
+                    y: bounds.y_min,
+                },
+                Point2 {
+                    x: bounds.x_max,
+                    y: bounds.y_max,
+                },
+                Point2 {
+                    x: bounds.x_min,
+                    y: bounds.y_max,
+                },
+            ],
+            ArenaShape::Circle => (0..CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = 2. * PI * i as f32 / CIRCLE_SEGMENTS as f32;
+                    Point2 {
+                        x: center.x + radius * angle.cos(),
+                        y: center.y + radius * angle.sin(),
+                    }
+                })
+                .collect(),
+            ArenaShape::Polygon(points) => {
+                points.iter().map(|point| bounds.place(*point)).collect()
+            }
+        }
+    }
+
+    /// Whether the point is inside the arena or on its border
+    pub fn contains(&self, bounds: ArenaBounds, point: Point2<f32>) -> bool {
+        match self {
+            ArenaShape::Rectangle => {
+                point.x >= bounds.x_min
+                    && point.x <= bounds.x_max
+                    && point.y >= bounds.y_min
+                    && point.y <= bounds.y_max
+            }
+            ArenaShape::Circle => {
+                let (center, radius) = bounds.inscribed();
+                super::distance(center, point) <= radius
+            }
+            ArenaShape::Polygon(_) => polygon_contains(&self.outline(bounds), point),
+        }
+    }
+
+    /// Distance from the point to the closest part of the border
+    pub fn border_distance(&self, bounds: ArenaBounds, point: Point2<f32>) -> f32 {
+        match self {
+            ArenaShape::Circle => {
+                let (center, radius) = bounds.inscribed();
+                (radius - super::distance(center, point)).abs()
+            }
+            _ => edges(&self.outline(bounds))
+                .map(|(a, b)| segment_distance(point, a, b))
+                .fold(f32::INFINITY, f32::min),
+        }
+    }
+
+    /// Whether any part of the bounding box is outside the arena
+    #[inline]
+    pub fn escapes(&self, bounds: ArenaBounds, bbox: BoundingBox) -> bool {
+        bbox.into_iter().any(|point| !self.contains(bounds, point))
+    }
+
+    /// If the curve's next position is outside the arena, move the curve to where the border
+    /// is on the opposite side of its heading. See [ArenaBounds::wrap].
+    pub fn wrap(&self, bounds: ArenaBounds, curve: &mut Curve, delta: f32) {
+        if let ArenaShape::Rectangle = self {
+            return bounds.wrap(curve, delta);
+        }
+
+        let bbox = BoundingBox::new(curve.next_pos(delta), curve.girth.as_f32());
+
+        if !self.escapes(bounds, bbox) {
+            return;
+        }
+
+        let margin = curve.girth.as_f32() + 1.;
+        let back = Point2 {
+            x: -curve.rotation.cos(),
+            y: -curve.rotation.sin(),
+        };
+
+        // The farthest border crossing behind the curve is where it comes back in
+        let exit = edges(&self.outline(bounds))
+            .filter_map(|(a, b)| ray_intersection(curve.position, back, a, b))
+            .fold(None, |farthest: Option<f32>, t| {
+                Some(farthest.map_or(t, |f| f.max(t)))
+            });
+
+        let Some(exit) = exit else {
+            curve.position = bounds.inscribed().0;
+            return;
+        };
+
+        let along = |travelled: f32| Point2 {
+            x: curve.position.x + back.x * travelled,
+            y: curve.position.y + back.y * travelled,
+        };
+
+        // Step further in when coming back in at a shallow angle, otherwise the bounding box
+        // would still poke out of the border and the curve would wrap right back. Where the
+        // arena is too narrow for that, come back in right at the exit.
+        let mut travelled = exit - margin;
+        let mut position = along(exit);
+
+        while travelled > 0. {
+            let candidate = along(travelled);
+
+            if !self.escapes(bounds, BoundingBox::new(candidate, curve.girth.as_f32())) {
+                position = candidate;
+                break;
+            }
+
+            travelled -= margin;
+        }
+
+        curve.position = position;
+    }
+
+    /// If the curve's next position is outside the arena, reflect its heading off the closest
+    /// part of the border. See [ArenaBounds::bounce].
+    pub fn bounce(&self, bounds: ArenaBounds, curve: &mut Curve, delta: f32) {
+        if let ArenaShape::Rectangle = self {
+            return bounds.bounce(curve, delta);
+        }
+
+        let next_pos = curve.next_pos(delta);
+
+        if !self.escapes(bounds, BoundingBox::new(next_pos, curve.girth.as_f32())) {
+            return;
+        }
+
+        let outline = self.outline(bounds);
+        let Some((a, b)) = edges(&outline).min_by(|(a1, b1), (a2, b2)| {
+            segment_distance(next_pos, *a1, *b1).total_cmp(&segment_distance(next_pos, *a2, *b2))
+        }) else {
+            return;
+        };
+
+        let (d_x, d_y) = (b.x - a.x, b.y - a.y);
+        let len = (d_x * d_x + d_y * d_y).sqrt();
+        let normal = (-d_y / len, d_x / len);
+
+        let (dir_x, dir_y) = (curve.rotation.cos(), curve.rotation.sin());
+        let dot = dir_x * normal.0 + dir_y * normal.1;
+
+        curve.rotation = (dir_y - 2. * dot * normal.1).atan2(dir_x - 2. * dot * normal.0);
+    }
+}
+
+/// Iterate over the edges of a closed outline
+#[inline]
+pub fn edges(outline: &[Point2<f32>]) -> impl Iterator<Item = (Point2<f32>, Point2<f32>)> + '_ {
+    outline
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (*a, outline[(i + 1) % outline.len()]))
+}
+
+/// Whether the point is inside the closed outline, using the even-odd rule
+pub fn polygon_contains(outline: &[Point2<f32>], point: Point2<f32>) -> bool {
+    // Count the edges crossed by a ray going right of the point
+    let mut inside = false;
+
+    for (a, b) in edges(outline) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+/// Distance from the point to the closest point of the segment `a`-`b`
+#[inline]
+pub fn segment_distance(point: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+    let (d_x, d_y) = (b.x - a.x, b.y - a.y);
+    let len_sq = d_x * d_x + d_y * d_y;
+
+    let t = if len_sq == 0. {
+        0.
+    } else {
+        (((point.x - a.x) * d_x + (point.y - a.y) * d_y) / len_sq).clamp(0., 1.)
+    };
+
+    super::distance(
+        point,
+        Point2 {
+            x: a.x + d_x * t,
+            y: a.y + d_y * t,
+        },
+    )
+}
+
+/// How far along the ray starting at `origin` going in `direction` the segment `a`-`b` is hit
+#[inline]
+fn ray_intersection(
+    origin: Point2<f32>,
+    direction: Point2<f32>,
+    a: Point2<f32>,
+    b: Point2<f32>,
+) -> Option<f32> {
+    let (e_x, e_y) = (b.x - a.x, b.y - a.y);
+    let denom = direction.x * e_y - direction.y * e_x;
+
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let (w_x, w_y) = (a.x - origin.x, a.y - origin.y);
+    let t = (w_x * e_y - w_y * e_x) / denom;
+    let u = (w_x * direction.y - w_y * direction.x) / denom;
+
+    (t >= 0. && (0. ..=1.).contains(&u)).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inscribed circle centered at (100, 100) with a radius of 100
+    const BOUNDS: ArenaBounds = ArenaBounds {
+        x_min: 0.,
+        x_max: 200.,
+        y_min: 0.,
+        y_max: 200.,
+    };
+
+    /// The outline of the cross map
+    fn cross() -> ArenaShape {
+        ArenaShape::Polygon(
+            [
+                (-0.35, -1.),
+                (0.35, -1.),
+                (0.35, -0.35),
+                (1., -0.35),
+                (1., 0.35),
+                (0.35, 0.35),
+                (0.35, 1.),
+                (-0.35, 1.),
+                (-0.35, 0.35),
+                (-1., 0.35),
+                (-1., -0.35),
+                (-0.35, -0.35),
+            ]
+            .into_iter()
+            .map(|(x, y)| Point2 { x, y })
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn concave_notch_is_outside() {
+        let shape = cross();
+
+        // Between the right and the bottom arm
+        assert!(!shape.contains(BOUNDS, BOUNDS.place(Point2 { x: 0.7, y: 0.7 })));
+        assert!(!shape.contains(BOUNDS, BOUNDS.place(Point2 { x: -0.5, y: -0.5 })));
+
+        assert!(shape.contains(BOUNDS, BOUNDS.place(Point2 { x: 0., y: 0. })));
+        assert!(shape.contains(BOUNDS, BOUNDS.place(Point2 { x: 0.9, y: 0.2 })));
+        assert!(shape.contains(BOUNDS, BOUNDS.place(Point2 { x: -0.2, y: 0.9 })));
+    }
+
+    #[test]
+    fn circle_border_distance() {
+        let shape = ArenaShape::Circle;
+
+        let cases = [
+            ((100., 100.), 100.),
+            ((150., 100.), 50.),
+            ((100., 10.), 10.),
+            ((200., 100.), 0.),
+            // Outside the arena
+            ((100., 230.), 30.),
+        ];
+
+        for ((x, y), expected) in cases {
+            let distance = shape.border_distance(BOUNDS, Point2 { x, y });
+            assert!(
+                (distance - expected).abs() < 1e-3,
+                "({x}, {y}) should be {expected} away from the border, got {distance}"
+            );
+        }
+    }
+
+    #[test]
+    fn shallow_wrap_lands_inside() {
+        let delta = 0.05;
+
+        // Each curve is right next to a border and barely heading out of it
+        let cases = [
+            (ArenaShape::Circle, (199., 100.), PI * 0.5 - 0.2),
+            (ArenaShape::regular(6), (186., 100.), PI * 0.5 - 0.2),
+            (cross(), (100., 198.), PI - 0.15),
+        ];
+
+        for (shape, (x, y), rotation) in cases {
+            let mut curve = Curve::at(Point2 { x, y }, rotation);
+            let girth = curve.girth.as_f32();
+
+            assert!(
+                shape.escapes(BOUNDS, BoundingBox::new(curve.next_pos(delta), girth)),
+                "{shape:?} should be left from ({x}, {y})"
+            );
+
+            shape.wrap(BOUNDS, &mut curve, delta);
+
+            assert_ne!(curve.position, Point2 { x, y });
+            assert!(
+                !shape.escapes(BOUNDS, BoundingBox::new(curve.position, girth)),
+                "{shape:?} wrapped from ({x}, {y}) to {:?}, outside the arena",
+                curve.position
+            );
+        }
+    }
+
+    #[test]
+    fn narrow_wrap_comes_back_in_at_the_exit() {
+        let delta = 0.05;
+        let shape = ArenaShape::regular(3);
+
+        // Right below the top corner of the triangle, heading out of its right side. The left
+        // side is closer behind the curve than the wrapping margin.
+        let mut curve = Curve::at(Point2 { x: 100., y: 2.5 }, 0.);
+
+        shape.wrap(BOUNDS, &mut curve, delta);
+
+        assert!(
+            curve.position.x < 100. && (curve.position.y - 2.5).abs() < 1e-3,
+            "should come back in on the left side, got {:?}",
+            curve.position
+        );
+        assert!(shape.border_distance(BOUNDS, curve.position) < 1e-3);
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::{vec_deque, VecDeque};
+
+use ggez::mint::Point2;
+
+use super::point::Line;
+
+/// The lines a curve leaves behind. Every tick the trail is active pushes a single line.
+///
+/// Collisions and drawing both read the points straight from the trail, so erasing points
+/// here is all it takes to remove a part of the trail from the game.
+#[derive(Debug, Default, Clone)]
+pub struct Trail(VecDeque<Line>);
+
+impl Trail {
+    #[inline]
+    pub fn push(&mut self, line: Line) {
+        self.0.push_back(line);
+    }
+
+    /// Remove the oldest line
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<Line> {
+        self.0.pop_front()
+    }
+
+    /// Amount of lines in the trail, including erased ones
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> vec_deque::Iter<'_, Line> {
+        self.0.iter()
+    }
+
+    /// Remove the whole trail
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Remove every point within `radius` of `center`.
+    ///
+    /// Lines are kept even when all their points get erased so the line count keeps matching the
+    /// amount of ticks the trail was drawn in, which the self collision check relies on.
+    pub fn erase_circle(&mut self, center: Point2<f32>, radius: f32) {
+        let radius_sq = radius * radius;
+
+        for line in self.0.iter_mut() {
+            line.points.retain(|point| {
+                let (d_x, d_y) = (point.x - center.x, point.y - center.y);
+                d_x * d_x + d_y * d_y > radius_sq
+            });
+        }
+    }
+}
@@ -3,9 +3,11 @@ use ggez::conf::WindowMode;
 use ggez::event::{self};
 use ggez::GameResult;
 
+mod audio;
 mod context;
 mod kurve;
 mod menu;
+mod resource;
 
 pub fn main() -> GameResult {
     let mut cb = ggez::ContextBuilder::new("curve", "biblius");
@@ -1,60 +1,123 @@
+use crate::audio::{AudioSettings, VolumeChannel};
 use ggez::graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale};
+use ggez::input::keyboard::KeyCode;
 use ggez::mint::Point2;
 use ggez::{Context, GameResult};
 use std::fmt::Debug;
 
 #[derive(Debug)]
 pub struct MainMenu {
-    pub items: [MainMenuItem; 1],
+    pub items: [MainMenuItem; 5],
     pub selected: usize,
 }
 
 impl MainMenu {
     pub fn new() -> Self {
         Self {
-            items: [MainMenuItem::PlayButton { size: (200., 60.) }],
+            items: [
+                MainMenuItem::PlayButton { size: (200., 60.) },
+                MainMenuItem::EditorButton { size: (200., 60.) },
+                MainMenuItem::Volume {
+                    channel: VolumeChannel::Master,
+                    size: (300., 40.),
+                },
+                MainMenuItem::Volume {
+                    channel: VolumeChannel::Effects,
+                    size: (300., 40.),
+                },
+                MainMenuItem::Volume {
+                    channel: VolumeChannel::Music,
+                    size: (300., 40.),
+                },
+            ],
             selected: 0,
         }
     }
 
-    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+    /// Handle up/down navigation and adjust the selected volume with left/right
+    pub fn navigate(&mut self, ctx: &mut Context, settings: &mut AudioSettings) {
+        if ctx.keyboard.is_key_just_pressed(KeyCode::Up) {
+            if self.selected == 0 {
+                self.selected = self.items.len() - 1;
+            } else {
+                self.selected -= 1;
+            }
+        }
+
+        if ctx.keyboard.is_key_just_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+
+        if let MainMenuItem::Volume { channel, .. } = self.items[self.selected] {
+            if ctx.keyboard.is_key_just_pressed(KeyCode::Left) {
+                settings.decrement(channel);
+            }
+
+            if ctx.keyboard.is_key_just_pressed(KeyCode::Right) {
+                settings.increment(channel);
+            }
+        }
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        settings: &AudioSettings,
+    ) -> GameResult {
         let (x, y) = ctx.gfx.drawable_size();
         let center = Point2 {
             x: x * 0.5,
             y: y * 0.5,
         };
 
-        for item in self.items.iter() {
-            match item {
-                MainMenuItem::PlayButton { size } => {
-                    let rect = graphics::Rect::new(
-                        center.x - size.0 * 0.5,
-                        center.y - size.1 * 0.5,
-                        size.0,
-                        size.1,
-                    );
-
-                    let mut text = graphics::Text::new("Play");
-                    text.set_scale(PxScale::from(24.));
-                    let text_dims = text.dimensions(ctx).unwrap();
-
-                    canvas.draw(
-                        &text,
-                        DrawParam::default().dest(Point2 {
-                            x: rect.x + size.0 * 0.5 - text_dims.w * 0.5,
-                            y: rect.y + size.1 * 0.5 - text_dims.h * 0.5,
-                        }),
-                    );
-
-                    let mesh = graphics::Mesh::new_rectangle(
-                        ctx,
-                        graphics::DrawMode::stroke(2.),
-                        rect,
-                        Color::WHITE,
-                    )?;
-
-                    canvas.draw(&mesh, DrawParam::default());
+        let mut offset = 0.;
+
+        for (i, item) in self.items.iter().enumerate() {
+            let (label, size) = match item {
+                MainMenuItem::PlayButton { size } => ("Play".to_string(), size),
+                MainMenuItem::EditorButton { size } => ("Map editor".to_string(), size),
+                MainMenuItem::Volume { channel, size } => {
+                    let volume = (settings.get(*channel) * 100.).round();
+                    let muted = matches!(channel, VolumeChannel::Music) && settings.music_muted;
+                    if muted {
+                        (format!("< {channel}: {volume}% (muted) >"), size)
+                    } else {
+                        (format!("< {channel}: {volume}% >"), size)
+                    }
                 }
+            };
+
+            let rect = graphics::Rect::new(
+                center.x - size.0 * 0.5,
+                center.y - size.1 * 0.5 + offset,
+                size.0,
+                size.1,
+            );
+
+            offset += size.1 * 1.5;
+
+            let mut text = graphics::Text::new(label);
+            text.set_scale(PxScale::from(24.));
+            let text_dims = text.dimensions(ctx).unwrap();
+
+            canvas.draw(
+                &text,
+                DrawParam::default().dest(Point2 {
+                    x: rect.x + size.0 * 0.5 - text_dims.w * 0.5,
+                    y: rect.y + size.1 * 0.5 - text_dims.h * 0.5,
+                }),
+            );
+
+            if self.selected == i {
+                let mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.),
+                    rect,
+                    Color::WHITE,
+                )?;
+
+                canvas.draw(&mesh, DrawParam::default());
             }
         }
 
@@ -70,5 +133,14 @@ impl Default for MainMenu {
 
 #[derive(Debug)]
 pub enum MainMenuItem {
-    PlayButton { size: (f32, f32) },
+    PlayButton {
+        size: (f32, f32),
+    },
+    EditorButton {
+        size: (f32, f32),
+    },
+    Volume {
+        channel: VolumeChannel,
+        size: (f32, f32),
+    },
 }
@@ -0,0 +1,103 @@
+use ggez::{audio::Source, graphics::Image, Context, GameResult};
+use std::{borrow::Cow, collections::HashMap};
+
+/// A single file in the resources directory, for assets that belong to something other than an
+/// asset bank. With the `embed` feature its contents are compiled into the binary, the same as
+/// for the banks. Create them with [resource!][crate::resource!].
+#[derive(Debug, Clone, Copy)]
+pub struct Resource {
+    /// Path in the game's filesystem
+    pub path: &'static str,
+
+    #[cfg(feature = "embed")]
+    pub bytes: &'static [u8],
+}
+
+impl Resource {
+    pub fn image(&self, ctx: &mut Context) -> GameResult<Image> {
+        #[cfg(feature = "embed")]
+        return Image::from_bytes(ctx, self.bytes);
+
+        #[cfg(not(feature = "embed"))]
+        Image::from_path(ctx, self.path)
+    }
+
+    pub fn sound(&self, ctx: &mut Context) -> GameResult<Source> {
+        #[cfg(feature = "embed")]
+        return Source::from_data(ctx, ggez::audio::SoundData::from_bytes(self.bytes));
+
+        #[cfg(not(feature = "embed"))]
+        Source::new(ctx, self.path)
+    }
+
+    /// Raw contents of the file
+    #[cfg_attr(feature = "embed", allow(unused_variables))]
+    pub fn bytes(&self, ctx: &mut Context) -> GameResult<Cow<'static, [u8]>> {
+        #[cfg(feature = "embed")]
+        return Ok(Cow::Borrowed(self.bytes));
+
+        #[cfg(not(feature = "embed"))]
+        {
+            use std::io::Read;
+
+            let mut bytes = vec![];
+            ctx.fs.open(self.path)?.read_to_end(&mut bytes)?;
+            Ok(Cow::Owned(bytes))
+        }
+    }
+
+    /// Whether the file can be loaded. Embedded resources always can.
+    #[cfg_attr(feature = "embed", allow(unused_variables))]
+    pub fn exists(&self, ctx: &Context) -> bool {
+        #[cfg(feature = "embed")]
+        return true;
+
+        #[cfg(not(feature = "embed"))]
+        ctx.fs.is_file(self.path)
+    }
+}
+
+/// Refer to a file by its path relative to the resources directory
+#[macro_export]
+macro_rules! resource {
+    ($path:literal) => {
+        $crate::resource::Resource {
+            path: concat!("/", $path),
+            #[cfg(feature = "embed")]
+            bytes: include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../resources/", $path)),
+        }
+    };
+}
+
+/// Assets loaded from [Resource]s up front, looked up by the resource they came from. Resources
+/// used more than once are only loaded once.
+#[derive(Debug)]
+pub struct ResourceCache<T>(HashMap<&'static str, T>);
+
+impl<T> ResourceCache<T> {
+    pub fn load(
+        ctx: &mut Context,
+        resources: impl IntoIterator<Item = Resource>,
+        load: impl Fn(&Resource, &mut Context) -> GameResult<T>,
+    ) -> GameResult<Self> {
+        let mut assets = HashMap::new();
+
+        for resource in resources {
+            if !assets.contains_key(resource.path) {
+                assets.insert(resource.path, load(&resource, ctx)?);
+            }
+        }
+
+        Ok(Self(assets))
+    }
+
+    #[inline]
+    pub fn get(&self, resource: Resource) -> Option<&T> {
+        self.0.get(resource.path)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, resource: Resource) -> Option<&mut T> {
+        self.0.get_mut(resource.path)
+    }
+}
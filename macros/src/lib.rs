@@ -1,73 +1,377 @@
-use proc_macro_error::abort;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use std::path::PathBuf;
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, DeriveInput, ExprLit, ExprTuple, Ident, Lit,
-    MetaNameValue, Token,
+    punctuated::Punctuated, spanned::Spanned, Attribute, DeriveInput, Error, Expr, ExprLit, Fields,
+    Ident, Lit, LitStr, MetaNameValue, Token,
 };
 
-#[proc_macro_derive(ImageBank, attributes(image, scale))]
-pub fn image_bank(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    struct ImageBankMeta<'a> {
-        field_id: &'a Ident,
-        path: String,
+/// Generates a `new` constructor loading every annotated field from the resources directory.
+///
+/// - `#[image(path = "..", scale = (x, y))]` loads a `ggez::graphics::Image`. If a scale is given,
+///   a `<field>_scale` accessor returning it as a `ggez::glam::Vec2` is generated.
+/// - `#[sound(path = "..")]` loads a `ggez::audio::Source`.
+/// - `#[font(path = "..", name = "..")]` registers the font with the graphics context and stores
+///   its name in a `&'static str` field. The name defaults to the field name.
+///
+/// Paths are relative to the `resources` directory. With the `embed` feature the assets are
+/// compiled into the binary instead of being read at runtime, and every path is checked to exist
+/// at compile time. The directory is searched for starting from the deriving crate's manifest
+/// directory and going up, so it can live in the workspace root.
+#[proc_macro_derive(AssetBank, attributes(image, sound, font))]
+pub fn asset_bank(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
     }
-    let input: DeriveInput = syn::parse(input).expect("invalid input for image bank");
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AssetKind {
+    Image,
+    Sound,
+    Font,
+}
+
+impl AssetKind {
+    fn from_attr(attr: &Attribute) -> Option<Self> {
+        let path = attr.meta.path();
+        if path.is_ident("image") {
+            Some(Self::Image)
+        } else if path.is_ident("sound") {
+            Some(Self::Sound)
+        } else if path.is_ident("font") {
+            Some(Self::Font)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::Image => "image",
+            AssetKind::Sound => "sound",
+            AssetKind::Font => "font",
+        }
+    }
+
+    /// Keys accepted in the attribute
+    fn extra_keys(&self) -> &'static str {
+        match self {
+            AssetKind::Image => "`path` or `scale`",
+            AssetKind::Sound => "`path`",
+            AssetKind::Font => "`path` or `name`",
+        }
+    }
+}
+
+/// The directory assets are loaded from
+const RESOURCES_DIR: &str = "resources";
+
+struct Asset<'a> {
+    field: &'a Ident,
+    kind: AssetKind,
+    path: String,
+    path_span: Span,
+    scale: Option<(f32, f32)>,
+    name: Option<String>,
+}
+
+impl Asset<'_> {
+    /// Expression evaluating to the asset loaded from the game's filesystem
+    fn load(&self) -> TokenStream {
+        let path = &self.path;
+        match self.kind {
+            AssetKind::Image => quote!(ggez::graphics::Image::from_path(ctx, #path)?),
+            AssetKind::Sound => quote!(ggez::audio::Source::new(ctx, #path)?),
+            AssetKind::Font => quote!(ggez::graphics::FontData::from_path(ctx, #path)?),
+        }
+    }
+
+    /// Expression evaluating to the asset built from bytes included in the binary
+    fn load_embedded(&self, file: &str) -> TokenStream {
+        match self.kind {
+            AssetKind::Image => {
+                quote!(ggez::graphics::Image::from_bytes(
+                    ctx,
+                    include_bytes!(#file)
+                )?)
+            }
+            AssetKind::Sound => quote!(ggez::audio::Source::from_data(
+                ctx,
+                ggez::audio::SoundData::from_bytes(include_bytes!(#file))
+            )?),
+            AssetKind::Font => quote!(ggez::graphics::FontData::from_slice(include_bytes!(#file))?),
+        }
+    }
+}
+
+/// Resolve the asset path to a file in the deriving crate's resources directory
+fn resource_file(asset: &Asset) -> syn::Result<String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        Error::new(
+            asset.path_span,
+            "cannot embed assets without `CARGO_MANIFEST_DIR` set",
+        )
+    })?;
+
+    let manifest_dir = PathBuf::from(manifest_dir);
+
+    let Some(resources) = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join(RESOURCES_DIR))
+        .find(|dir| dir.is_dir())
+    else {
+        return Err(Error::new(
+            asset.path_span,
+            format!(
+                "no `{RESOURCES_DIR}` directory found in `{}` or its parents",
+                manifest_dir.display()
+            ),
+        ));
+    };
+
+    let file = resources.join(asset.path.trim_start_matches('/'));
+
+    if !file.is_file() {
+        return Err(Error::new(
+            asset.path_span,
+            format!("asset `{}` does not exist", file.display()),
+        ));
+    }
+
+    Ok(file.display().to_string())
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
     let id = &input.ident;
-    let syn::Data::Struct(data) = input.data else {
-        abort!(input.span(), "image bank works only on structs")
+
+    let syn::Data::Struct(ref data) = input.data else {
+        return Err(Error::new(
+            input.span(),
+            "asset bank can only be derived for structs",
+        ));
     };
 
-    let mut meta = vec![];
+    let Fields::Named(ref fields) = data.fields else {
+        return Err(Error::new(
+            data.fields.span(),
+            "asset bank fields must be named",
+        ));
+    };
+
+    let mut assets = vec![];
+    let mut errors: Option<Error> = None;
+
+    for field in fields.named.iter() {
+        let name = field.ident.as_ref().unwrap();
+        let mut found = false;
 
-    for field in data.fields.iter() {
         for attr in field.attrs.iter() {
-            if attr.meta.path().is_ident("image") {
-                let name = field.ident.as_ref().unwrap();
-                let mut bank = ImageBankMeta {
-                    field_id: name,
-                    path: String::new(),
-                };
-
-                let pairs = attr.meta.require_list().expect("must be list");
-                let punct = pairs
-                    .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
-                    .expect("must be name value pairs");
-
-                for item in punct {
-                    if item.path.is_ident("path") {
-                        let syn::Expr::Lit(ExprLit { ref lit, .. }) = item.value else {
-                            abort!(item.span(), "path must be str lit")
-                        };
-                        let Lit::Str(str) = lit else {
-                            abort!(lit.span(), "must be str lit")
-                        };
-                        bank.path = format!("/{}", str.value());
-                    }
-
-                    if item.path.is_ident("scale") {
-                        let syn::Expr::Tuple(ExprTuple { ref elems, .. }) = item.value else {
-                            abort!(item.span(), "path must be str lit")
-                        };
-                    }
-                }
-                meta.push(bank);
+            let Some(kind) = AssetKind::from_attr(attr) else {
+                continue;
+            };
+
+            if found {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        attr.span(),
+                        format!("field `{name}` can only hold a single asset"),
+                    ),
+                );
+                continue;
             }
+            found = true;
+
+            match parse_asset(name, kind, attr) {
+                Ok(asset) => assets.push(asset),
+                Err(e) => push_error(&mut errors, e),
+            }
+        }
+
+        if !found {
+            push_error(
+                &mut errors,
+                Error::new(
+                    field.span(),
+                    format!(
+                        "field `{name}` needs an `#[image]`, `#[sound]` or `#[font]` attribute"
+                    ),
+                ),
+            );
         }
     }
 
-    let tokens = meta.iter().map(|bank| {
-        let field = &bank.field_id;
-        let img_path = &bank.path;
-        quote::quote!(#field: ggez::graphics::Image::from_path(ctx, #img_path)?,)
+    let mut fields = vec![];
+
+    for asset in assets.iter() {
+        let field = asset.field;
+
+        let load = if cfg!(feature = "embed") {
+            match resource_file(asset) {
+                Ok(file) => asset.load_embedded(&file),
+                Err(e) => {
+                    push_error(&mut errors, e);
+                    continue;
+                }
+            }
+        } else {
+            asset.load()
+        };
+
+        fields.push(match asset.kind {
+            AssetKind::Image | AssetKind::Sound => quote!(#field: #load,),
+            AssetKind::Font => {
+                let name = asset.name.clone().unwrap_or_else(|| field.to_string());
+                quote!(#field: {
+                    ctx.gfx.add_font(#name, #load);
+                    #name
+                },)
+            }
+        });
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let scales = assets.iter().filter_map(|asset| {
+        let (x, y) = asset.scale?;
+        let accessor = format_ident!("{}_scale", asset.field);
+        Some(quote!(
+            #[inline]
+            pub fn #accessor(&self) -> ggez::glam::Vec2 {
+                ggez::glam::Vec2::new(#x, #y)
+            }
+        ))
     });
 
-    quote::quote!(
+    Ok(quote!(
         impl #id {
             pub fn new(ctx: &mut ggez::Context) -> Result<Self, ggez::GameError> {
                 Ok(Self {
-                    #(#tokens)*
+                    #(#fields)*
                 })
             }
+
+            #(#scales)*
+        }
+    ))
+}
+
+fn push_error(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+fn parse_asset<'a>(field: &'a Ident, kind: AssetKind, attr: &Attribute) -> syn::Result<Asset<'a>> {
+    let mut asset = Asset {
+        field,
+        kind,
+        path: String::new(),
+        path_span: attr.span(),
+        scale: None,
+        name: None,
+    };
+
+    let list = attr.meta.require_list().map_err(|_| {
+        Error::new(
+            attr.span(),
+            format!("expected `#[{}(path = \"..\")]`", kind.as_str()),
+        )
+    })?;
+
+    let items = list.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)?;
+
+    let mut path = None;
+
+    for item in items {
+        if item.path.is_ident("path") {
+            if path.is_some() {
+                return Err(Error::new(item.path.span(), "duplicate `path`"));
+            }
+            let lit = str_lit(&item.value, "`path` must be a string literal")?;
+            if lit.value().is_empty() {
+                return Err(Error::new(lit.span(), "`path` must not be empty"));
+            }
+            path = Some(lit.value());
+            asset.path_span = lit.span();
+        } else if item.path.is_ident("scale") && kind == AssetKind::Image {
+            if asset.scale.is_some() {
+                return Err(Error::new(item.path.span(), "duplicate `scale`"));
+            }
+            asset.scale = Some(parse_scale(&item.value)?);
+        } else if item.path.is_ident("name") && kind == AssetKind::Font {
+            if asset.name.is_some() {
+                return Err(Error::new(item.path.span(), "duplicate `name`"));
+            }
+            let lit = str_lit(&item.value, "`name` must be a string literal")?;
+            asset.name = Some(lit.value());
+        } else {
+            return Err(Error::new(
+                item.path.span(),
+                format!(
+                    "unknown `{}` attribute key, expected {}",
+                    kind.as_str(),
+                    kind.extra_keys()
+                ),
+            ));
         }
-    )
-    .into()
+    }
+
+    let Some(path) = path else {
+        return Err(Error::new(
+            attr.span(),
+            format!("missing `path = \"..\"` in `{}` attribute", kind.as_str()),
+        ));
+    };
+
+    asset.path = format!("/{}", path.trim_start_matches('/'));
+
+    Ok(asset)
+}
+
+fn str_lit<'a>(expr: &'a Expr, msg: &str) -> syn::Result<&'a LitStr> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit),
+        _ => Err(Error::new(expr.span(), msg)),
+    }
+}
+
+fn parse_scale(expr: &Expr) -> syn::Result<(f32, f32)> {
+    const MSG: &str = "`scale` must be a tuple of two positive numbers, e.g. `scale = (0.5, 0.5)`";
+
+    let Expr::Tuple(tuple) = expr else {
+        return Err(Error::new(expr.span(), MSG));
+    };
+
+    if tuple.elems.len() != 2 {
+        return Err(Error::new(tuple.span(), MSG));
+    }
+
+    let mut scale = [0.; 2];
+
+    for (value, elem) in scale.iter_mut().zip(tuple.elems.iter()) {
+        let Expr::Lit(ExprLit { lit, .. }) = elem else {
+            return Err(Error::new(elem.span(), MSG));
+        };
+
+        *value = match lit {
+            Lit::Float(f) => f.base10_parse()?,
+            Lit::Int(i) => i.base10_parse::<u32>()? as f32,
+            _ => return Err(Error::new(lit.span(), MSG)),
+        };
+
+        if *value <= 0. {
+            return Err(Error::new(lit.span(), MSG));
+        }
+    }
+
+    Ok((scale[0], scale[1]))
 }